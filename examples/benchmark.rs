@@ -33,17 +33,17 @@ fn runnable_with_parq_enabled(){
     use tsxlib::timeutils;
 
 
-    fn datapoint_gen_func(row: &parquet::record::Row) -> TimeSeriesDataPoint<NaiveDateTime,f64> {
-        let value = row.get_double(1).unwrap();
-        let istamp = row.get_timestamp_millis(0).unwrap() as i64;
+    fn datapoint_gen_func(row: &parquet::record::Row) -> Result<TimeSeriesDataPoint<NaiveDateTime,f64>, parquet::errors::ParquetError> {
+        let value = row.get_double(1)?;
+        let istamp = row.get_timestamp_millis(0)? as i64;
         let ndt = timeutils::naive_datetime_from_millis(istamp);
-        TimeSeriesDataPoint::new(ndt,value)
+        Ok(TimeSeriesDataPoint::new(ndt,value))
     };
 
     
-    gen_timings!("Read Parquet Test", Box::new(|| {tsxlib::io::parquet::read_from_file::<NaiveDateTime, f64>("../../../testdata/rand_data.parquet",datapoint_gen_func).unwrap();}),10);
+    gen_timings!("Read Parquet Test", Box::new(|| {tsxlib::io::parquet::read_from_file::<NaiveDateTime, f64, _>("../../../testdata/rand_data.parquet",datapoint_gen_func).unwrap();}),10);
 
-    let ts: TimeSeries<NaiveDateTime,f64>  = tsxlib::io::parquet::read_from_file::<NaiveDateTime, f64>("../../../testdata/rand_data.parquet",datapoint_gen_func).unwrap();
+    let ts: TimeSeries<NaiveDateTime,f64>  = tsxlib::io::parquet::read_from_file::<NaiveDateTime, f64, _>("../../../testdata/rand_data.parquet",datapoint_gen_func).unwrap();
 
     
     let tsrres: TimeSeries<NaiveDateTime,f64> = ts.into_iter().map(|x| TimeSeriesDataPoint::new(x.timestamp,x.value * 2.0)).collect_from_unchecked_iter();
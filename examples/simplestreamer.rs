@@ -11,14 +11,14 @@ fn runnable_with_parq_enabled(){
     use std::sync::mpsc;
     use std::thread;
     
-    fn datapoint_gen_func(row: &parquet::record::Row) -> TimeSeriesDataPoint<NaiveDateTime,f64> {
-        let value = row.get_double(1).unwrap();
-        let istamp = row.get_timestamp_millis(0).unwrap() as i64;
+    fn datapoint_gen_func(row: &parquet::record::Row) -> Result<TimeSeriesDataPoint<NaiveDateTime,f64>, parquet::errors::ParquetError> {
+        let value = row.get_double(1)?;
+        let istamp = row.get_timestamp_millis(0)? as i64;
         let ts = timeutils::naive_datetime_from_millis(istamp);
-        TimeSeriesDataPoint::new(ts,value)
+        Ok(TimeSeriesDataPoint::new(ts,value))
     };
 
-    let ts = tsxlib::io::parquet::read_from_file::<NaiveDateTime, f64>("../../../testdata/rand_data.parquet",datapoint_gen_func).unwrap();
+    let ts = tsxlib::io::parquet::read_from_file::<NaiveDateTime, f64, _>("../../../testdata/rand_data.parquet",datapoint_gen_func).unwrap();
     let (sender, mut receiver): (mpsc::Sender<TimeSeriesDataPoint<NaiveDateTime,f64>>,mpsc::Receiver<TimeSeriesDataPoint<NaiveDateTime,f64>>) = mpsc::channel();
     thread::spawn(move || {
         ts.into_ordered_iter().for_each( |dp| {
@@ -4,4 +4,5 @@
 //!
 pub mod chrono_utils;
 pub mod int_utils;
+pub mod time_distance;
 pub mod macros;
\ No newline at end of file
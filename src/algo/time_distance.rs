@@ -0,0 +1,379 @@
+//! # Distance between keys
+//!
+//! The `TimeDistance` trait measures the signed distance between two keys of an index, it is what the generic as-of comparators are built on.
+//! Implement it for your own time struct to get tolerance based as-of merges.
+//!
+//! The `TimeStep` trait builds on it to move keys by a distance, which is what regular grids (resampling, reindexing, date ranges) are laid out with.
+use std::cmp;
+use std::fmt;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, DateTime, TimeZone, Utc};
+
+/// Signed distance between two keys of an index
+pub trait TimeDistance {
+    /// the type of the distance, i.e. chrono::Duration for chrono timestamps
+    type Delta: Copy + Ord + fmt::Debug + 'static;
+    /// returns `self - other`
+    fn delta(&self, other: &Self) -> Self::Delta;
+    /// the zero distance
+    fn zero_delta() -> Self::Delta;
+}
+
+/// Keys that can be moved by a distance, the step of a grid has to be positive
+pub trait TimeStep: TimeDistance + Sized {
+    /// returns `self + delta`, saturating at the bounds of the key type
+    fn add_delta(&self, delta: &Self::Delta) -> Self;
+    /// the number of whole `step`s in `delta`, rounded towards negative infinity
+    fn steps_in(delta: &Self::Delta, step: &Self::Delta) -> i64;
+    /// returns `step * n`
+    fn scale_delta(step: &Self::Delta, n: i64) -> Self::Delta;
+    /// returns `delta / over` as a fraction, i.e. how far a key is between two others
+    fn delta_ratio(delta: &Self::Delta, over: &Self::Delta) -> f64;
+}
+
+// the deltas are widened so that `self - other` can not overflow
+macro_rules! time_distance_int_impl {
+    ($delta:ty => $($t:ty)*) => ($(
+        impl TimeDistance for $t {
+            type Delta = $delta;
+            #[inline]
+            fn delta(&self, other: &Self) -> $delta {
+                *self as $delta - *other as $delta
+            }
+            #[inline]
+            fn zero_delta() -> $delta {
+                0
+            }
+        }
+
+        impl TimeStep for $t {
+            #[inline]
+            fn add_delta(&self, delta: &$delta) -> $t {
+                (*self as $delta).saturating_add(*delta).clamp(<$t>::MIN as $delta, <$t>::MAX as $delta) as $t
+            }
+            #[inline]
+            fn steps_in(delta: &$delta, step: &$delta) -> i64 {
+                delta.div_euclid(*step).clamp(i64::MIN as $delta, i64::MAX as $delta) as i64
+            }
+            #[inline]
+            fn scale_delta(step: &$delta, n: i64) -> $delta {
+                step.saturating_mul(n as $delta)
+            }
+            #[inline]
+            fn delta_ratio(delta: &$delta, over: &$delta) -> f64 {
+                *delta as f64 / *over as f64
+            }
+        }
+    )*)
+}
+time_distance_int_impl! { i64 => i8 i16 i32 u8 u16 u32 }
+time_distance_int_impl! { i128 => i64 u64 isize usize }
+
+impl TimeDistance for i128 {
+    type Delta = i128;
+    #[inline]
+    fn delta(&self, other: &Self) -> i128 {
+        self.saturating_sub(*other)
+    }
+    #[inline]
+    fn zero_delta() -> i128 {
+        0
+    }
+}
+
+impl TimeStep for i128 {
+    fn add_delta(&self, delta: &i128) -> i128 {
+        self.saturating_add(*delta)
+    }
+    fn steps_in(delta: &i128, step: &i128) -> i64 {
+        delta.div_euclid(*step).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+    fn scale_delta(step: &i128, n: i64) -> i128 {
+        step.saturating_mul(n as i128)
+    }
+    fn delta_ratio(delta: &i128, over: &i128) -> f64 {
+        *delta as f64 / *over as f64
+    }
+}
+
+impl TimeDistance for u128 {
+    type Delta = i128;
+    #[inline]
+    fn delta(&self, other: &Self) -> i128 {
+        match self >= other {
+            true => cmp::min(self - other, i128::MAX as u128) as i128,
+            false => -(cmp::min(other - self, i128::MAX as u128) as i128)
+        }
+    }
+    #[inline]
+    fn zero_delta() -> i128 {
+        0
+    }
+}
+
+impl TimeStep for u128 {
+    fn add_delta(&self, delta: &i128) -> u128 {
+        match *delta >= 0 {
+            true => self.saturating_add(*delta as u128),
+            false => self.saturating_sub(delta.unsigned_abs())
+        }
+    }
+    fn steps_in(delta: &i128, step: &i128) -> i64 {
+        delta.div_euclid(*step).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+    fn scale_delta(step: &i128, n: i64) -> i128 {
+        step.saturating_mul(n as i128)
+    }
+    fn delta_ratio(delta: &i128, over: &i128) -> f64 {
+        *delta as f64 / *over as f64
+    }
+}
+
+fn duration_to_nanos(duration: &Duration) -> i128 {
+    duration.num_seconds() as i128 * 1_000_000_000 + duration.subsec_nanos() as i128
+}
+
+// saturates at the bounds of Duration instead of panicking
+fn duration_from_nanos(nanos: i128) -> Duration {
+    let nanos = nanos.clamp(duration_to_nanos(&Duration::MIN), duration_to_nanos(&Duration::MAX));
+    Duration::new(nanos.div_euclid(1_000_000_000) as i64, nanos.rem_euclid(1_000_000_000) as u32).unwrap_or(match nanos < 0 {
+        true => Duration::MIN,
+        false => Duration::MAX
+    })
+}
+
+macro_rules! time_step_chrono_impl {
+    ($($t:ty)*) => ($(
+        impl TimeStep for $t {
+            fn add_delta(&self, delta: &Duration) -> $t {
+                self.checked_add_signed(*delta).unwrap_or(match *delta < Duration::zero() {
+                    true => <$t>::MIN,
+                    false => <$t>::MAX
+                })
+            }
+            fn steps_in(delta: &Duration, step: &Duration) -> i64 {
+                duration_to_nanos(delta).div_euclid(duration_to_nanos(step)).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+            }
+            fn scale_delta(step: &Duration, n: i64) -> Duration {
+                duration_from_nanos(duration_to_nanos(step).saturating_mul(n as i128))
+            }
+            fn delta_ratio(delta: &Duration, over: &Duration) -> f64 {
+                duration_to_nanos(delta) as f64 / duration_to_nanos(over) as f64
+            }
+        }
+    )*)
+}
+time_step_chrono_impl! { NaiveDateTime NaiveDate }
+
+impl<TZInfo: TimeZone> TimeStep for DateTime<TZInfo> {
+    fn add_delta(&self, delta: &Duration) -> DateTime<TZInfo> {
+        self.clone().checked_add_signed(*delta).unwrap_or_else(|| match *delta < Duration::zero() {
+            true => DateTime::<Utc>::MIN_UTC.with_timezone(&self.timezone()),
+            false => DateTime::<Utc>::MAX_UTC.with_timezone(&self.timezone())
+        })
+    }
+    fn steps_in(delta: &Duration, step: &Duration) -> i64 {
+        duration_to_nanos(delta).div_euclid(duration_to_nanos(step)).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+    fn scale_delta(step: &Duration, n: i64) -> Duration {
+        duration_from_nanos(duration_to_nanos(step).saturating_mul(n as i128))
+    }
+    fn delta_ratio(delta: &Duration, over: &Duration) -> f64 {
+        duration_to_nanos(delta) as f64 / duration_to_nanos(over) as f64
+    }
+}
+
+impl TimeDistance for NaiveDateTime {
+    type Delta = Duration;
+    fn delta(&self, other: &Self) -> Duration {
+        self.signed_duration_since(*other)
+    }
+    fn zero_delta() -> Duration {
+        Duration::nanoseconds(0)
+    }
+}
+
+impl TimeDistance for NaiveDate {
+    type Delta = Duration;
+    fn delta(&self, other: &Self) -> Duration {
+        self.signed_duration_since(*other)
+    }
+    fn zero_delta() -> Duration {
+        Duration::nanoseconds(0)
+    }
+}
+
+impl<TZInfo: TimeZone> TimeDistance for DateTime<TZInfo> {
+    type Delta = Duration;
+    fn delta(&self, other: &Self) -> Duration {
+        self.clone().signed_duration_since(other.clone())
+    }
+    fn zero_delta() -> Duration {
+        Duration::nanoseconds(0)
+    }
+}
+
+fn merge_asof_prior_impl<TDate: TimeDistance + cmp::Eq>(this: &TDate,other: &TDate,other_prior: &TDate, asoflookback :TDate::Delta) -> (cmp::Ordering,i64) {
+    let diff = this.delta(other_prior);
+    match  diff {
+        d if d < TDate::zero_delta() && this != other => (cmp::Ordering::Less,0),
+        d if d > asoflookback && this != other => (cmp::Ordering::Greater,0),
+        d if d <= asoflookback && this != other => (cmp::Ordering::Equal,-1),
+        _ => (cmp::Ordering::Equal,0)
+    }
+}
+
+fn merge_asof_fwd_impl<TDate: TimeDistance + cmp::Eq>(this: &TDate,other: &TDate,other_peak: &TDate, asoflookfwd :TDate::Delta) -> (cmp::Ordering,i64) {
+    let diff1 = other_peak.delta(this);
+    let diff2 = other.delta(this);
+    let zerodur = TDate::zero_delta();
+    let diff = cmp::min(diff1,cmp::max(diff2,zerodur));
+    let offset:i64 = if diff == diff2 {0}else{1};
+    match  diff {
+        d if d < zerodur && this != other => (cmp::Ordering::Greater,0),
+        d if d > asoflookfwd && this != other => (cmp::Ordering::Less,0),
+        d if d <= asoflookfwd && this != other => (cmp::Ordering::Equal,offset),
+        _ => (cmp::Ordering::Equal,0)
+    }
+}
+
+/// NearestTieBreak decides which observation wins when the prior and the following observation are equally close
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum NearestTieBreak{ Prior, Following}
+
+fn merge_asof_nearest_impl<TDate: TimeDistance + cmp::Ord>(this: &TDate,other: &TDate,other_prior: &TDate, tolerance :Option<TDate::Delta>, tie_break: NearestTieBreak) -> (cmp::Ordering,i64) {
+    if this == other {
+        return (cmp::Ordering::Equal,0);
+    }
+    // other is the first observation after this (if there is one), other_prior the last one before it (if there is one)
+    let following = if other > this { Some(other.delta(this)) } else { None };
+    let prior = if other_prior < this { Some(this.delta(other_prior)) } else { None };
+    let (diff, offset) = match (prior, following) {
+        (Some(p), Some(f)) if p < f || (p == f && tie_break == NearestTieBreak::Prior) => (p,-1),
+        (_, Some(f)) => (f,0),
+        (Some(p), None) => (p,-1),
+        (None, None) => return (cmp::Ordering::Greater,0)
+    };
+    match tolerance {
+        Some(tol) if diff > tol => (cmp::Ordering::Greater,0),
+        _ => (cmp::Ordering::Equal,offset)
+    }
+}
+
+/// Boxed comparator used by `TimeSeries::merge_apply_asof`, it is called with the key of this series, the candidate key of the other series and the key before the candidate and returns the match ordering and the offset to apply to the candidate position
+pub type AsofCompareFunc<TDate> = Box<dyn Fn(&TDate,&TDate,&TDate)->(cmp::Ordering,i64)>;
+
+/// Implementation of mergeasof for a given lookback for a pair of Timeseries whose index implements `TimeDistance`
+pub fn merge_asof_prior<TDate: TimeDistance + cmp::Eq + 'static>(look_back :TDate::Delta) -> AsofCompareFunc<TDate> {
+    Box::new(move |this: &TDate, other: &TDate, other_prior: &TDate| merge_asof_prior_impl(this, other, other_prior, look_back))
+}
+/// Implementation of mergeasof for a given look-forward for a pair of Timeseries whose index implements `TimeDistance`
+pub fn merge_asof_fwd<TDate: TimeDistance + cmp::Eq + 'static>(look_fwd :TDate::Delta) -> AsofCompareFunc<TDate> {
+    Box::new(move |this: &TDate, other: &TDate, other_peak: &TDate| merge_asof_fwd_impl(this, other, other_peak, look_fwd))
+}
+
+/// Implementation of mergeasof that matches the nearest observation (prior or following) within an optional tolerance for a pair of Timeseries whose index implements `TimeDistance`. Use it with `MergeAsofMode::Nearest`
+pub fn merge_asof_nearest<TDate: TimeDistance + cmp::Ord + 'static>(tolerance :Option<TDate::Delta>, tie_break: NearestTieBreak) -> AsofCompareFunc<TDate> {
+    Box::new(move |this: &TDate, other: &TDate, other_prior: &TDate| merge_asof_nearest_impl(this, other, other_prior, tolerance, tie_break))
+}
+
+/// -----------------------------------------------------------------------------------------------------------------------------------------
+/// Unit Test Area
+/// -----------------------------------------------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_int_deltas() {
+        assert_eq!(5u8.delta(&250u8), -245i64);
+        assert_eq!(i32::MIN.delta(&i32::MAX), i32::MIN as i64 - i32::MAX as i64);
+        assert_eq!(0u64.delta(&u64::MAX), -(u64::MAX as i128));
+        assert_eq!(i64::MAX.delta(&i64::MIN), i64::MAX as i128 - i64::MIN as i128);
+        assert_eq!(u128::MAX.delta(&0u128), i128::MAX);
+        assert_eq!(0u128.delta(&u128::MAX), -i128::MAX);
+        assert_eq!(i128::MIN.delta(&1), i128::MIN);
+    }
+
+    #[test]
+    fn test_chrono_deltas() {
+        let ndt = NaiveDate::from_ymd_opt(2021, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!((ndt + Duration::seconds(90)).delta(&ndt), Duration::seconds(90));
+        assert_eq!(ndt.date().delta(&(ndt.date() + Duration::days(2))), Duration::days(-2));
+        let dt = Utc.from_utc_datetime(&ndt);
+        assert_eq!(dt.delta(&(dt + Duration::milliseconds(5))), Duration::milliseconds(-5));
+    }
+
+    #[test]
+    fn test_time_steps() {
+        assert_eq!(5u8.add_delta(&-3), 2u8);
+        assert_eq!(i64::steps_in(&-7, &5), -2);
+        assert_eq!(u32::steps_in(&10, &5), 2);
+        assert_eq!(i32::scale_delta(&5, -3), -15);
+        assert_eq!(10u128.add_delta(&-4), 6);
+        let ndt = NaiveDate::from_ymd_opt(2021, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(ndt.add_delta(&Duration::minutes(90)), NaiveDate::from_ymd_opt(2021, 1, 1).unwrap().and_hms_opt(1, 30, 0).unwrap());
+        assert_eq!(NaiveDateTime::steps_in(&Duration::milliseconds(-1500), &Duration::seconds(1)), -2);
+        assert_eq!(NaiveDateTime::steps_in(&Duration::days(1), &Duration::minutes(15)), 96);
+        assert_eq!(NaiveDateTime::scale_delta(&Duration::milliseconds(1500), -3), Duration::milliseconds(-4500));
+        let dt = Utc.from_utc_datetime(&ndt);
+        assert_eq!(dt.add_delta(&Duration::seconds(1)).delta(&dt), Duration::seconds(1));
+        assert_eq!(i32::delta_ratio(&1, &4), 0.25);
+        assert_eq!(NaiveDateTime::delta_ratio(&Duration::seconds(90), &Duration::minutes(1)), 1.5);
+    }
+
+    #[test]
+    fn test_time_steps_saturate() {
+        assert_eq!(250u8.add_delta(&10), u8::MAX);
+        assert_eq!(5u8.add_delta(&-10), 0);
+        assert_eq!(i8::MAX.add_delta(&1), i8::MAX);
+        assert_eq!(i16::MIN.add_delta(&-1), i16::MIN);
+        assert_eq!(u32::MAX.add_delta(&i64::MAX), u32::MAX);
+        assert_eq!(i32::MIN.add_delta(&i64::MIN), i32::MIN);
+        assert_eq!(i64::MAX.add_delta(&i128::MAX), i64::MAX);
+        assert_eq!(0u64.add_delta(&i128::MIN), 0);
+        assert_eq!(usize::MAX.add_delta(&1), usize::MAX);
+        assert_eq!(i128::MAX.add_delta(&1), i128::MAX);
+        assert_eq!(u128::MAX.add_delta(&1), u128::MAX);
+        assert_eq!(i64::scale_delta(&i128::MAX, 2), i128::MAX);
+        assert_eq!(u8::scale_delta(&i64::MAX, -2), i64::MIN);
+        assert_eq!(u64::steps_in(&(u64::MAX as i128), &1), i64::MAX);
+        assert_eq!(NaiveDateTime::MAX.add_delta(&Duration::seconds(1)), NaiveDateTime::MAX);
+        assert_eq!(NaiveDate::MIN.add_delta(&Duration::days(-1)), NaiveDate::MIN);
+        assert_eq!(DateTime::<Utc>::MAX_UTC.add_delta(&Duration::seconds(1)), DateTime::<Utc>::MAX_UTC);
+        // chrono deltas saturate at the bounds of Duration
+        assert_eq!(NaiveDateTime::scale_delta(&Duration::MAX, 2), Duration::MAX);
+        assert_eq!(NaiveDateTime::scale_delta(&Duration::days(1), i64::MAX), Duration::MAX);
+        assert_eq!(NaiveDate::scale_delta(&Duration::days(1), i64::MIN), Duration::MIN);
+        assert_eq!(NaiveDate::scale_delta(&Duration::MIN, 1), Duration::MIN);
+        assert_eq!(DateTime::<Utc>::scale_delta(&Duration::MAX, -1), -Duration::MAX);
+        assert_eq!(NaiveDateTime::steps_in(&Duration::MAX, &Duration::nanoseconds(1)), i64::MAX);
+        assert_eq!(NaiveDate::steps_in(&Duration::MIN, &Duration::nanoseconds(1)), i64::MIN);
+        assert_eq!(DateTime::<Utc>::steps_in(&Duration::MAX, &Duration::MIN), -1);
+    }
+
+    #[test]
+    fn test_generic_comparators() {
+        // prior: this is 10, the candidate prior is 8
+        let prior = merge_asof_prior::<u64>(2);
+        assert_eq!(prior(&10, &12, &8), (cmp::Ordering::Equal, -1));
+        assert_eq!(prior(&10, &12, &7), (cmp::Ordering::Greater, 0));
+        assert_eq!(prior(&10, &10, &8), (cmp::Ordering::Equal, 0));
+        // fwd: this is 10, the candidate is 12
+        let fwd = merge_asof_fwd::<i64>(2);
+        assert_eq!(fwd(&10, &12, &15), (cmp::Ordering::Equal, 0));
+        assert_eq!(fwd(&10, &13, &15), (cmp::Ordering::Less, 0));
+        assert_eq!(fwd(&10, &9, &9), (cmp::Ordering::Greater, 0));
+        // nearest: this is 10
+        let nearest = merge_asof_nearest::<i32>(Some(3), NearestTieBreak::Prior);
+        assert_eq!(nearest(&10, &12, &9), (cmp::Ordering::Equal, -1));
+        assert_eq!(nearest(&10, &11, &8), (cmp::Ordering::Equal, 0));
+        assert_eq!(nearest(&10, &12, &8), (cmp::Ordering::Equal, -1));
+        assert_eq!(nearest(&10, &14, &5), (cmp::Ordering::Greater, 0));
+        assert_eq!(nearest(&10, &10, &8), (cmp::Ordering::Equal, 0));
+        let nearest = merge_asof_nearest::<i32>(None, NearestTieBreak::Following);
+        assert_eq!(nearest(&10, &12, &8), (cmp::Ordering::Equal, 0));
+        assert_eq!(nearest(&10, &8, &8), (cmp::Ordering::Equal, -1));
+        assert_eq!(nearest(&10, &20, &20), (cmp::Ordering::Equal, 0));
+    }
+}
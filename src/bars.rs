@@ -0,0 +1,214 @@
+//! # OHLCV Bars
+//!
+//! Free funcs that turn a series of ticks into open/high/low/close/volume bars. The price and size of a tick are read via accessor closures so any `T` works,
+//! for the common `(price, size)` tuple use `|t: &(f64, f64)| t.0` and `|t: &(f64, f64)| t.1`.
+//!
+//! Time bars are labeled with the start of their bucket, all other bars with the timestamp of their last tick.
+//! For tick, volume and dollar bars the last bar is emitted even if it did not reach its threshold.
+use chrono::Duration;
+use serde::{Serialize, Deserialize};
+use std::cmp;
+use std::hash::Hash;
+
+use crate::timeseries::TimeSeries;
+use crate::timeutils::{round_down_to_nearest_duration, DurationRoudable};
+use crate::index::HashableIndex;
+
+/// An OHLCV bar, `value` is the traded value (sum of price * size), `vwap` the volume weighted average price and `trades` the number of ticks in the bar. A bar without volume has a NaN vwap
+#[derive(Clone,Copy,Debug,PartialEq,Serialize,Deserialize)]
+pub struct Bar {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub value: f64,
+    pub vwap: f64,
+    pub trades: usize
+}
+
+impl Bar {
+    fn from_tick(price: f64, size: f64) -> Bar {
+        Bar { open: price, high: price, low: price, close: price, volume: size, value: price * size, vwap: f64::NAN, trades: 1 }
+    }
+
+    fn add_tick(&mut self, price: f64, size: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += size;
+        self.value += price * size;
+        self.trades += 1;
+    }
+
+    fn finish(mut self) -> Bar {
+        self.vwap = match self.volume != 0.0 {
+            true => self.value / self.volume,
+            false => f64::NAN
+        };
+        self
+    }
+}
+
+/// Builds bars by adding ticks to the current bar until `is_done` says it is complete, bars are labeled with the timestamp of their last tick
+fn threshold_bars<TDate, T, FPrice, FSize, FDone>(ts: &TimeSeries<TDate,T>, price_func: FPrice, size_func: FSize, is_done: FDone) -> TimeSeries<TDate,Bar>
+where
+    TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord,
+    T: Clone,
+    FPrice: Fn(&T) -> f64,
+    FSize: Fn(&T) -> f64,
+    FDone: Fn(&Bar) -> bool
+{
+    let mut stamps: Vec<TDate> = Vec::new();
+    let mut bars: Vec<Bar> = Vec::new();
+    let mut current: Option<Bar> = None;
+    for (stamp, tick) in ts.index().iter().zip(ts.values().iter()) {
+        let (price, size) = (price_func(tick), size_func(tick));
+        let bar = match current.take() {
+            Some(mut bar) => {
+                bar.add_tick(price, size);
+                bar
+            },
+            None => Bar::from_tick(price, size)
+        };
+        match is_done(&bar) {
+            true => {
+                stamps.push(stamp.clone());
+                bars.push(bar.finish());
+            },
+            false => current = Some(bar)
+        }
+    }
+    if let Some(bar) = current {
+        stamps.push(ts.index()[ts.len() - 1].clone());
+        bars.push(bar.finish());
+    }
+    TimeSeries::from_vecs_unchecked(HashableIndex::new(stamps), bars)
+}
+
+/// Bars over fixed time buckets, i.e. 5 minute bars, labeled with the start of the bucket. Buckets without ticks are skipped
+///
+/// # Example
+///
+/// ```
+/// use tsxlib::timeseries::TimeSeries;
+/// use tsxlib::bars;
+/// use chrono::{Duration, NaiveDateTime};
+///
+/// let index: Vec<NaiveDateTime> = tsxlib::index::HashableIndex::from_int_stamps(vec![0, 30, 61, 90]).into_values();
+/// let ts = TimeSeries::from_vecs(index, vec![(10.0, 1.0), (12.0, 3.0), (11.0, 2.0), (9.0, 2.0)]).unwrap();
+/// let bars = bars::time_bars(&ts, Duration::minutes(1), |t| t.0, |t| t.1);
+/// assert_eq!(bars.len(), 2);
+/// assert_eq!(bars.values()[0].high, 12.0);
+/// assert_eq!(bars.values()[0].vwap, 11.5);
+/// assert_eq!(bars.values()[1].trades, 2);
+/// ```
+pub fn time_bars<TDate, T, FPrice, FSize>(ts: &TimeSeries<TDate,T>, bar_size: Duration, price_func: FPrice, size_func: FSize) -> TimeSeries<TDate,Bar>
+where
+    TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord + DurationRoudable<TDate>,
+    T: Clone,
+    FPrice: Fn(&T) -> f64,
+    FSize: Fn(&T) -> f64
+{
+    let mut stamps: Vec<TDate> = Vec::new();
+    let mut bars: Vec<Bar> = Vec::new();
+    for (stamp, tick) in ts.index().iter().zip(ts.values().iter()) {
+        let (price, size) = (price_func(tick), size_func(tick));
+        let bucket = round_down_to_nearest_duration(stamp, &bar_size);
+        match stamps.last() {
+            Some(last) if *last == bucket => bars.last_mut().unwrap().add_tick(price, size),
+            _ => {
+                stamps.push(bucket);
+                bars.push(Bar::from_tick(price, size));
+            }
+        }
+    }
+    let bars = bars.into_iter().map(Bar::finish).collect();
+    TimeSeries::from_vecs_unchecked(HashableIndex::new(stamps), bars)
+}
+
+/// Bars of `ticks_per_bar` ticks each
+pub fn tick_bars<TDate, T, FPrice, FSize>(ts: &TimeSeries<TDate,T>, ticks_per_bar: usize, price_func: FPrice, size_func: FSize) -> TimeSeries<TDate,Bar>
+where
+    TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord,
+    T: Clone,
+    FPrice: Fn(&T) -> f64,
+    FSize: Fn(&T) -> f64
+{
+    threshold_bars(ts, price_func, size_func, |bar| bar.trades >= ticks_per_bar)
+}
+
+/// Bars that close on the tick that brings their volume to at least `volume_per_bar`
+pub fn volume_bars<TDate, T, FPrice, FSize>(ts: &TimeSeries<TDate,T>, volume_per_bar: f64, price_func: FPrice, size_func: FSize) -> TimeSeries<TDate,Bar>
+where
+    TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord,
+    T: Clone,
+    FPrice: Fn(&T) -> f64,
+    FSize: Fn(&T) -> f64
+{
+    threshold_bars(ts, price_func, size_func, |bar| bar.volume >= volume_per_bar)
+}
+
+/// Bars that close on the tick that brings their traded value (price * size) to at least `value_per_bar`
+pub fn dollar_bars<TDate, T, FPrice, FSize>(ts: &TimeSeries<TDate,T>, value_per_bar: f64, price_func: FPrice, size_func: FSize) -> TimeSeries<TDate,Bar>
+where
+    TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord,
+    T: Clone,
+    FPrice: Fn(&T) -> f64,
+    FSize: Fn(&T) -> f64
+{
+    threshold_bars(ts, price_func, size_func, |bar| bar.value >= value_per_bar)
+}
+
+/// -----------------------------------------------------------------------------------------------------------------------------------------
+/// Unit Test Area
+/// -----------------------------------------------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn ticks() -> TimeSeries<NaiveDateTime,(f64,f64)> {
+        let index = HashableIndex::from_int_stamps(vec![0, 10, 50, 65, 70, 200]).into_values();
+        TimeSeries::from_vecs(index, vec![(10.0, 1.0), (11.0, 2.0), (9.0, 1.0), (10.0, 4.0), (12.0, 1.0), (8.0, 3.0)]).unwrap()
+    }
+
+    #[test]
+    fn test_time_bars() {
+        let bars = time_bars(&ticks(), Duration::minutes(1), |t| t.0, |t| t.1);
+        assert_eq!(bars.index().values(), HashableIndex::from_int_stamps(vec![0, 60, 180]).into_values());
+        assert_eq!(bars.values()[0], Bar { open: 10.0, high: 11.0, low: 9.0, close: 9.0, volume: 4.0, value: 41.0, vwap: 10.25, trades: 3 });
+        assert_eq!(bars.values()[1], Bar { open: 10.0, high: 12.0, low: 10.0, close: 12.0, volume: 5.0, value: 52.0, vwap: 10.4, trades: 2 });
+        assert_eq!(bars.values()[2].trades, 1);
+    }
+
+    #[test]
+    fn test_threshold_bars() {
+        let ts = ticks();
+        let by_ticks = tick_bars(&ts, 4, |t| t.0, |t| t.1);
+        assert_eq!(by_ticks.index().values(), HashableIndex::from_int_stamps(vec![65, 200]).into_values());
+        assert_eq!(by_ticks.values().iter().map(|b| b.trades).collect::<Vec<usize>>(), vec![4, 2]);
+        assert_eq!(by_ticks.values()[0].close, 10.0);
+
+        // the tick that crosses the threshold belongs to the bar it closes
+        let by_volume = volume_bars(&ts, 3.0, |t| t.0, |t| t.1);
+        assert_eq!(by_volume.values().iter().map(|b| b.volume).collect::<Vec<f64>>(), vec![3.0, 5.0, 4.0]);
+        assert_eq!(by_volume.index().values(), HashableIndex::from_int_stamps(vec![10, 65, 200]).into_values());
+
+        let by_value = dollar_bars(&ts, 40.0, |t| t.0, |t| t.1);
+        assert_eq!(by_value.values().iter().map(|b| b.value).collect::<Vec<f64>>(), vec![41.0, 40.0, 36.0]);
+        assert_eq!(by_value.values()[0].vwap, 10.25);
+    }
+
+    #[test]
+    fn test_bars_edge_cases() {
+        let empty: TimeSeries<NaiveDateTime,(f64,f64)> = TimeSeries::empty();
+        assert!(time_bars(&empty, Duration::minutes(1), |t| t.0, |t| t.1).is_empty());
+        assert!(volume_bars(&empty, 1.0, |t| t.0, |t| t.1).is_empty());
+
+        let no_volume = TimeSeries::from_vecs(vec![1, 2], vec![(1.0, 0.0), (2.0, 0.0)]).unwrap();
+        let bars = tick_bars(&no_volume, 5, |t| t.0, |t| t.1);
+        assert_eq!(bars.len(), 1);
+        assert!(bars.values()[0].vwap.is_nan());
+    }
+}
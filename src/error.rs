@@ -0,0 +1,120 @@
+//! # Error Types
+use std::error::Error;
+use std::fmt;
+
+/// TsxError is the error type returned by the TimeSeries constructors and the IO methods
+#[derive(Debug)]
+pub enum TsxError {
+    /// the index and the values that a series was built with do not have the same length
+    LengthMismatch { index: usize, values: usize },
+    /// the index stops increasing at `position`, i.e. the element at `position` is smaller than the one before it
+    NonMonotonicIndex { position: usize },
+    /// the element of the index at `position` is equal to the one before it
+    DuplicateTimestamp { position: usize },
+    /// a parameter such as a decay, a quantile or a sample size is out of range, `value` is the offending value and `expected` the valid range
+    InvalidParameter { name: &'static str, value: String, expected: &'static str },
+    /// a file could not be opened, read or written, this also covers the io failures raised inside the csv, parquet and json readers and writers
+    Io(std::io::Error),
+    /// a csv record could not be read or written, `row` is the 0 based record number (excluding the header) and `column` is the 0 based field index
+    Csv { row: Option<u64>, column: Option<u64>, source: csv::Error },
+    /// a parquet file or one of its rows could not be read, `row` is the 0 based row number
+    #[cfg(feature = "parq")]
+    Parquet { row: Option<u64>, source: parquet::errors::ParquetError },
+    /// a json document could not be read or written, `line` and `column` are 1 based positions in the document
+    #[cfg(feature = "json")]
+    Json { line: usize, column: usize, source: serde_json::Error },
+}
+
+impl fmt::Display for TsxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TsxError::LengthMismatch { index, values } => write!(f, "length mismatch: index has {} elements but there are {} values", index, values),
+            TsxError::NonMonotonicIndex { position } => write!(f, "index is not monotonically increasing at position {}", position),
+            TsxError::DuplicateTimestamp { position } => write!(f, "index has a duplicate timestamp at position {}", position),
+            TsxError::InvalidParameter { name, value, expected } => write!(f, "invalid {} {}, it has to be {}", name, value, expected),
+            TsxError::Io(e) => write!(f, "io error: {}", e),
+            TsxError::Csv { row, column, source } => {
+                write!(f, "csv error")?;
+                if let Some(row) = row {
+                    write!(f, " at row {}", row)?;
+                }
+                if let Some(column) = column {
+                    write!(f, " in column {}", column)?;
+                }
+                write!(f, ": {}", source)
+            },
+            #[cfg(feature = "parq")]
+            TsxError::Parquet { row, source } => match row {
+                Some(row) => write!(f, "parquet error at row {}: {}", row, source),
+                None => write!(f, "parquet error: {}", source),
+            },
+            #[cfg(feature = "json")]
+            TsxError::Json { line, column, source } => write!(f, "json error at line {} column {}: {}", line, column, source),
+        }
+    }
+}
+
+impl Error for TsxError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TsxError::Io(e) => Some(e),
+            TsxError::Csv { source, .. } => Some(source),
+            #[cfg(feature = "parq")]
+            TsxError::Parquet { source, .. } => Some(source),
+            #[cfg(feature = "json")]
+            TsxError::Json { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TsxError {
+    fn from(e: std::io::Error) -> Self {
+        TsxError::Io(e)
+    }
+}
+
+impl From<csv::Error> for TsxError {
+    fn from(e: csv::Error) -> Self {
+        if e.is_io_error() {
+            return match e.into_kind() {
+                csv::ErrorKind::Io(ioe) => TsxError::Io(ioe),
+                kind => TsxError::Io(std::io::Error::other(format!("{:?}", kind))),
+            };
+        }
+        let column = match e.kind() {
+            csv::ErrorKind::Deserialize { err, .. } => err.field(),
+            _ => None,
+        };
+        TsxError::Csv { row: None, column, source: e }
+    }
+}
+
+#[cfg(feature = "parq")]
+impl From<parquet::errors::ParquetError> for TsxError {
+    fn from(e: parquet::errors::ParquetError) -> Self {
+        TsxError::Parquet { row: None, source: e }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for TsxError {
+    fn from(e: serde_json::Error) -> Self {
+        if e.is_io() {
+            return TsxError::Io(e.into());
+        }
+        TsxError::Json { line: e.line(), column: e.column(), source: e }
+    }
+}
+
+impl TsxError {
+    /// attach a row number to an error raised while reading or writing a record, errors that are not tied to a record are returned as is
+    pub fn at_row(self, at: u64) -> TsxError {
+        match self {
+            TsxError::Csv { column, source, .. } => TsxError::Csv { row: Some(at), column, source },
+            #[cfg(feature = "parq")]
+            TsxError::Parquet { source, .. } => TsxError::Parquet { row: Some(at), source },
+            other => other
+        }
+    }
+}
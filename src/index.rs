@@ -3,13 +3,17 @@ use std::cmp;
 use std::collections::{BinaryHeap,HashMap, HashSet};
 use std::ops::Index;
 use std::hash::Hash;
-use chrono::{Duration, NaiveDateTime};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
 use serde::{Serialize};
 
+use crate::error::TsxError;
+use crate::algo::time_distance::TimeStep;
+use crate::timeutils::{self, CalendarDate};
+
 /// a HashableIndex<TDate> serves as the index for a timeseries, it requires that the index element be Serializatable (via serde), Hashable, Cloneable, Equatable, and Orderable.
 #[derive(Clone, Debug)]
 pub struct HashableIndex<TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> {
-    pub values: Vec<TIndex>
+    values: Vec<TIndex>
 }
 
 //SRC:: https://stackoverflow.com/questions/64262297/rust-how-to-find-n-th-most-frequent-element-in-a-collection
@@ -29,6 +33,55 @@ where
     heap.into_sorted_vec().iter().map(|r| r.0.clone()).collect()
 }
 
+/// CalendarFrequency describes the calendar aware grids of `HashableIndex::calendar_range`, `BusinessDay` is every weekday while the others are the last day of every month, quarter or year
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum CalendarFrequency{ BusinessDay, MonthEnd, QuarterEnd, YearEnd}
+
+// the calendar helpers return None past NaiveDate::MAX so that the ranges stop there
+fn first_calendar_date(from: NaiveDate, freq: CalendarFrequency) -> Option<NaiveDate> {
+    match freq {
+        CalendarFrequency::BusinessDay => match timeutils::is_weekend(&from) {
+            true => next_calendar_date(from, freq),
+            false => Some(from)
+        },
+        CalendarFrequency::MonthEnd => timeutils::last_day_of_month(from.year(), from.month()),
+        CalendarFrequency::QuarterEnd => timeutils::last_day_of_month(from.year(), from.month0() / 3 * 3 + 3),
+        CalendarFrequency::YearEnd => timeutils::last_day_of_month(from.year(), 12)
+    }
+}
+
+fn next_calendar_date(date: NaiveDate, freq: CalendarFrequency) -> Option<NaiveDate> {
+    let months_ahead = |months: u32| {
+        let month0 = date.month0() + months;
+        timeutils::last_day_of_month(date.year() + (month0 / 12) as i32, month0 % 12 + 1)
+    };
+    match freq {
+        CalendarFrequency::BusinessDay => {
+            let mut next = date.succ_opt()?;
+            while timeutils::is_weekend(&next) {
+                next = next.succ_opt()?;
+            }
+            Some(next)
+        },
+        CalendarFrequency::MonthEnd => months_ahead(1),
+        CalendarFrequency::QuarterEnd => months_ahead(3),
+        CalendarFrequency::YearEnd => months_ahead(12)
+    }
+}
+
+/// error out on steps that are not positive, a range can not move forward with them
+pub(crate) fn check_step<TIndex: TimeStep>(step: TIndex::Delta) -> Result<(), TsxError> {
+    match step > TIndex::zero_delta() {
+        true => Ok(()),
+        false => Err(TsxError::InvalidParameter{ name: "step", value: format!("{:?}", step), expected: "> 0" })
+    }
+}
+
+/// the dates of the calendar grid from the given date onwards
+fn calendar_dates(from: NaiveDate, freq: CalendarFrequency) -> impl Iterator<Item = NaiveDate> {
+    std::iter::successors(first_calendar_date(from, freq), move |date| next_calendar_date(*date, freq))
+}
+
 /// This trait represents an index that has a notion of sampleability. i.e. the semantic meaning of differenencces in the index
 pub trait SampleableIndex<TIndex: Serialize + Hash + Copy + cmp::Eq + cmp::Ord,TInterval>{
     fn sample_rates(&self) -> Vec<(usize, TInterval)>;
@@ -106,6 +159,115 @@ impl <TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> HashableIndex<TInde
 
 
 
+    /// The keys of the index, in order
+    pub fn values(&self) -> &[TIndex] {
+        &self.values
+    }
+
+    /// Take the keys out of the index
+    pub fn into_values(self) -> Vec<TIndex> {
+        self.values
+    }
+
+    /// The keys for the mutating methods of the series, which keep the index sorted and unique themselves
+    pub(crate) fn values_mut(&mut self) -> &mut Vec<TIndex> {
+        &mut self.values
+    }
+
+    /// Create a regular index of `step` spaced keys from `start` up to and including `end`, returns an error if `step` is not positive
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::index::HashableIndex;
+    /// use chrono::{Duration,NaiveDate};
+    ///
+    /// let start = NaiveDate::from_ymd_opt(2021, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// let index = HashableIndex::date_range(start, start + Duration::minutes(50), Duration::minutes(15)).unwrap();
+    /// assert_eq!(index.len(), 4);
+    /// assert_eq!(index[3], start + Duration::minutes(45));
+    /// ```
+    pub fn date_range(start: TIndex, end: TIndex, step: TIndex::Delta) -> Result<HashableIndex<TIndex>, TsxError>
+    where
+        TIndex: TimeStep
+    {
+        check_step::<TIndex>(step)?;
+        let mut values: Vec<TIndex> = Vec::new();
+        let mut key = start;
+        while key <= end {
+            let next = key.add_delta(&step);
+            values.push(key);
+            // keys that saturate can not move any further
+            if next <= values[values.len() - 1] {
+                break;
+            }
+            key = next;
+        }
+        Ok(HashableIndex { values })
+    }
+
+    /// Create a regular index of `periods` keys that are `step` apart, starting at `start`. Like `date_range` it stops at the last key that fits in the key type, so it can return fewer keys
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::index::HashableIndex;
+    ///
+    /// let index = HashableIndex::periods(10i64, 3, 5).unwrap();
+    /// assert_eq!(index.values(), vec![10, 15, 20]);
+    /// ```
+    pub fn periods(start: TIndex, periods: usize, step: TIndex::Delta) -> Result<HashableIndex<TIndex>, TsxError>
+    where
+        TIndex: TimeStep
+    {
+        check_step::<TIndex>(step)?;
+        // keys that saturate can not move any further
+        let values = std::iter::successors(Some(start), |key| Some(key.add_delta(&step)).filter(|next| next > key)).take(periods).collect();
+        Ok(HashableIndex { values })
+    }
+
+    /// Create a calendar aware index between `start` and `end` (inclusive), i.e. all business days or all month ends. The keys keep the time of day of `start`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::index::{HashableIndex,CalendarFrequency};
+    /// use chrono::NaiveDate;
+    ///
+    /// let index = HashableIndex::calendar_range(NaiveDate::from_ymd_opt(2021, 1, 15).unwrap(), NaiveDate::from_ymd_opt(2021, 4, 30).unwrap(), CalendarFrequency::MonthEnd);
+    /// assert_eq!(index.values(), vec![
+    ///     NaiveDate::from_ymd_opt(2021, 1, 31).unwrap(),
+    ///     NaiveDate::from_ymd_opt(2021, 2, 28).unwrap(),
+    ///     NaiveDate::from_ymd_opt(2021, 3, 31).unwrap(),
+    ///     NaiveDate::from_ymd_opt(2021, 4, 30).unwrap()
+    /// ]);
+    /// ```
+    pub fn calendar_range(start: TIndex, end: TIndex, freq: CalendarFrequency) -> HashableIndex<TIndex>
+    where
+        TIndex: CalendarDate
+    {
+        let last_date = end.local_date();
+        let values = calendar_dates(start.local_date(), freq)
+            .take_while(|date| *date <= last_date)
+            .filter_map(|date| start.with_local_date(date))
+            .filter(|key| *key >= start && *key <= end)
+            .collect();
+        HashableIndex { values }
+    }
+
+    /// Create a calendar aware index of `periods` keys from `start` onwards, see `calendar_range`
+    pub fn calendar_periods(start: TIndex, periods: usize, freq: CalendarFrequency) -> HashableIndex<TIndex>
+    where
+        TIndex: CalendarDate
+    {
+        let values = calendar_dates(start.local_date(), freq)
+            .filter_map(|date| start.with_local_date(date))
+            .filter(|key| *key >= start)
+            .take(periods)
+            .collect();
+        HashableIndex { values }
+    }
+
     /// test the monotonicity test for an index
     ///
     /// # Example
@@ -127,6 +289,31 @@ impl <TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> HashableIndex<TInde
             .all(|(x, y)| x < y)
     }
 
+    /// check that the index is strictly increasing, on failure the error points at the first offending position
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::index::HashableIndex;
+    /// use tsxlib::error::TsxError;
+    ///
+    /// let xs = HashableIndex::new(vec![1, 2, 3, 3]);
+    /// let ys = HashableIndex::new(vec![1, 2, 3, 2]);
+    /// assert!(HashableIndex::new(vec![1, 2, 3, 4]).check_monotonic().is_ok());
+    /// assert!(matches!(xs.check_monotonic(), Err(TsxError::DuplicateTimestamp{position: 3})));
+    /// assert!(matches!(ys.check_monotonic(), Err(TsxError::NonMonotonicIndex{position: 3})));
+    /// ```
+    pub fn check_monotonic(&self) -> Result<(), TsxError> {
+        for (pos, (x, y)) in self.values.iter().zip(self.values.iter().skip(1)).enumerate() {
+            match x.cmp(y) {
+                cmp::Ordering::Less => (),
+                cmp::Ordering::Equal => return Err(TsxError::DuplicateTimestamp{ position: pos + 1 }),
+                cmp::Ordering::Greater => return Err(TsxError::NonMonotonicIndex{ position: pos + 1 }),
+            }
+        }
+        Ok(())
+    }
+
 
     /// get length of the index
     pub fn len(&self) -> usize {
@@ -178,7 +365,7 @@ impl <TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> cmp::PartialEq for
 mod tests {
     use super::*;
     use crate::timeutils;
-    use chrono::{Duration};
+    use chrono::{Duration, TimeZone, Utc};
 
     #[test]
     fn test_increasing() {
@@ -214,4 +401,60 @@ mod tests {
         assert_eq!(index_mono.is_mono_intervaled(), true);
     }
 
+
+    #[test]
+    fn test_date_range() {
+        let start = NaiveDate::from_ymd_opt(2021, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let index = HashableIndex::date_range(start, start + Duration::hours(1), Duration::minutes(20)).unwrap();
+        assert_eq!(index, HashableIndex::periods(start, 4, Duration::minutes(20)).unwrap());
+        assert_eq!(index.last(), Some(&(start + Duration::hours(1))));
+        assert!(HashableIndex::date_range(start, start - Duration::days(1), Duration::days(1)).unwrap().is_empty());
+
+        let days = HashableIndex::date_range(NaiveDate::from_ymd_opt(2021, 2, 27).unwrap(), NaiveDate::from_ymd_opt(2021, 3, 2).unwrap(), Duration::days(1)).unwrap();
+        assert_eq!(days.len(), 4);
+        let dt = Utc.from_utc_datetime(&start);
+        assert_eq!(HashableIndex::periods(dt, 3, Duration::days(1)).unwrap()[2], dt + Duration::days(2));
+        assert_eq!(HashableIndex::date_range(u8::MAX - 2, u8::MAX, 2).unwrap().values, vec![253, 255]);
+        // periods stop at the top of the key type instead of wrapping around or repeating it
+        assert_eq!(HashableIndex::periods(254u8, 3, 1).unwrap().values, vec![254, 255]);
+        assert_eq!(HashableIndex::periods(i32::MAX - 1, 5, 1).unwrap().values, vec![i32::MAX - 1, i32::MAX]);
+        assert_eq!(HashableIndex::periods(u128::MAX, 2, 1).unwrap().values, vec![u128::MAX]);
+        assert!(HashableIndex::periods(254u8, 3, 1).unwrap().check_monotonic().is_ok());
+        assert_eq!(HashableIndex::periods(NaiveDateTime::MAX - Duration::seconds(1), 3, Duration::seconds(1)).unwrap().len(), 2);
+        // the step has to be positive
+        assert!(matches!(HashableIndex::date_range(0i64, 10, 0), Err(TsxError::InvalidParameter{ name: "step", .. })));
+        assert!(matches!(HashableIndex::periods(start, 3, Duration::seconds(-1)), Err(TsxError::InvalidParameter{ name: "step", .. })));
+    }
+
+    #[test]
+    fn test_calendar_range() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // 2021-01-01 is a friday
+        let bdays = HashableIndex::calendar_range(date(2021, 1, 1), date(2021, 1, 11), CalendarFrequency::BusinessDay);
+        assert_eq!(bdays.values, vec![date(2021, 1, 1), date(2021, 1, 4), date(2021, 1, 5), date(2021, 1, 6), date(2021, 1, 7), date(2021, 1, 8), date(2021, 1, 11)]);
+
+        let quarters = HashableIndex::calendar_range(date(2020, 2, 1), date(2021, 3, 30), CalendarFrequency::QuarterEnd);
+        assert_eq!(quarters.values, vec![date(2020, 3, 31), date(2020, 6, 30), date(2020, 9, 30), date(2020, 12, 31)]);
+        let years = HashableIndex::calendar_periods(date(2020, 12, 31), 2, CalendarFrequency::YearEnd);
+        assert_eq!(years.values, vec![date(2020, 12, 31), date(2021, 12, 31)]);
+
+        // keys keep the time of day of the start, a month end before the start time is skipped
+        let start = date(2021, 1, 31).and_hms_opt(16, 0, 0).unwrap();
+        let months = HashableIndex::calendar_periods(start, 3, CalendarFrequency::MonthEnd);
+        assert_eq!(months.values, vec![start, date(2021, 2, 28).and_hms_opt(16, 0, 0).unwrap(), date(2021, 3, 31).and_hms_opt(16, 0, 0).unwrap()]);
+        let months = HashableIndex::calendar_range(start, date(2021, 3, 31).and_hms_opt(12, 0, 0).unwrap(), CalendarFrequency::MonthEnd);
+        assert_eq!(months.len(), 2);
+
+        let dt = Utc.from_utc_datetime(&date(2021, 1, 2).and_hms_opt(9, 30, 0).unwrap());
+        let bdays = HashableIndex::calendar_periods(dt, 2, CalendarFrequency::BusinessDay);
+        assert_eq!(bdays.values, vec![Utc.from_utc_datetime(&date(2021, 1, 4).and_hms_opt(9, 30, 0).unwrap()), Utc.from_utc_datetime(&date(2021, 1, 5).and_hms_opt(9, 30, 0).unwrap())]);
+
+        // the grids stop at the last date chrono can represent
+        for freq in [CalendarFrequency::BusinessDay, CalendarFrequency::MonthEnd, CalendarFrequency::QuarterEnd, CalendarFrequency::YearEnd].iter() {
+            let near_max = HashableIndex::calendar_periods(NaiveDate::MAX - Duration::days(400), 1000, *freq);
+            assert!(!near_max.is_empty() && near_max.len() < 1000);
+            assert!(HashableIndex::calendar_range(NaiveDate::MAX - Duration::days(400), NaiveDate::MAX, *freq).len() <= near_max.len());
+        }
+        assert_eq!(HashableIndex::calendar_periods(NaiveDate::MAX, 2, CalendarFrequency::YearEnd).values, vec![NaiveDate::MAX]);
+    }
 }
@@ -1,32 +1,33 @@
 //! # CSV IO
 use serde::Serialize;
 use serde::de::DeserializeOwned;
-use std::error::Error;
 use std::cmp;
 use std::hash::Hash;
 
 use crate::{data_elements::TimeSeriesDataPoint, timeseries::TimeSeries};
+use crate::error::TsxError;
 
 
-/// Load series from the given CSV file
-pub fn read_from_file<TDate,T,TRecord>(file_path: &str, datapoint_gen_func: fn(TRecord)->TimeSeriesDataPoint<TDate,T>) -> Result<TimeSeries<TDate,T>, Box<dyn Error>> 
+/// Load series from the given CSV file, if a record can not be read the error will contain its row number (and column where available)
+pub fn read_from_file<TDate,T,TRecord,F>(file_path: &str, mut datapoint_gen_func: F) -> Result<TimeSeries<TDate,T>, TsxError> 
 where 
     TDate: Serialize + Hash + Copy + cmp::Eq + cmp::Ord, 
     T: Copy,
-    TRecord: DeserializeOwned + 'static 
+    TRecord: DeserializeOwned + 'static,
+    F: FnMut(TRecord)->TimeSeriesDataPoint<TDate,T>
 {
     let mut rdr = csv::Reader::from_path(file_path)?;
     let mut data: Vec<TimeSeriesDataPoint<TDate,T>> = Vec::new();
 
-    for result in rdr.deserialize() {
-        let record: TimeSeriesDataPoint<TDate,T> = datapoint_gen_func(result?);
+    for (row, result) in rdr.deserialize().enumerate() {
+        let record: TimeSeriesDataPoint<TDate,T> = datapoint_gen_func(result.map_err(|e| TsxError::from(e).at_row(row as u64))?);
         data.push(record);
     }
 
     Ok(TimeSeries::from_tsdatapoints_unchecked(data))
 }
 
-pub fn read_from_file_simple<TDate,T>(file_path: &str) -> Result<TimeSeries<TDate,T>, Box<dyn Error>> 
+pub fn read_from_file_simple<TDate,T>(file_path: &str) -> Result<TimeSeries<TDate,T>, TsxError> 
 where 
     TDate: DeserializeOwned + 'static  + Serialize + Hash + Copy + cmp::Eq + cmp::Ord, 
     T: DeserializeOwned + 'static  + Copy
@@ -34,22 +35,23 @@ where
     read_from_file(file_path,|tsdp|tsdp)
 }
 
-/// Save series as CSV file
-pub fn write_to_file<TDate,T,TRecord>(file_path: &str, ts: &TimeSeries<TDate,T>, record_gen_func: fn(TimeSeriesDataPoint<TDate,T>) ->TRecord ) -> Result<(), Box<dyn Error>> 
+/// Save series as CSV file, if a record can not be written the error will contain its row number
+pub fn write_to_file<TDate,T,TRecord,F>(file_path: &str, ts: &TimeSeries<TDate,T>, mut record_gen_func: F) -> Result<(), TsxError> 
 where 
     TDate: Serialize + Hash + Copy + cmp::Eq + cmp::Ord, 
     T: Copy,
-    TRecord: Serialize
+    TRecord: Serialize,
+    F: FnMut(TimeSeriesDataPoint<TDate,T>) ->TRecord
 {
     let mut wtr = csv::Writer::from_path(file_path)?;
-    for tsdp in ts.into_iter(){
-        wtr.serialize(&record_gen_func(tsdp)).unwrap();
+    for (row, tsdp) in ts.into_iter().enumerate(){
+        wtr.serialize(&record_gen_func(tsdp)).map_err(|e| TsxError::from(e).at_row(row as u64))?;
     }
     wtr.flush()?;
     Ok(())
 }
 /// Simple wrapper to save a timeseries to a csv, does not work when T is a nonprimitive type
-pub fn write_to_file_simple<TDate,T>(file_path: &str, ts: &TimeSeries<TDate,T>) -> Result<(), Box<dyn Error>> 
+pub fn write_to_file_simple<TDate,T>(file_path: &str, ts: &TimeSeries<TDate,T>) -> Result<(), TsxError> 
 where 
     TDate: Serialize + Hash + Copy + cmp::Eq + cmp::Ord, 
     T: Serialize + Copy
@@ -72,7 +74,7 @@ mod tests {
     #[test]
     fn test_readsimple() {
         let before = Instant::now();
-        let ts: Result<TimeSeries<NaiveDateTime,f64>, Box<dyn Error>>  = read_from_file_simple("testdata/large_justdoubles.csv"); //, "%Y-%m-%d %H:%M:%S%z"
+        let ts: Result<TimeSeries<NaiveDateTime,f64>, TsxError>  = read_from_file_simple("testdata/large_justdoubles.csv"); //, "%Y-%m-%d %H:%M:%S%z"
         println!("CSV Read Elapsed time: {:.2?}", before.elapsed());
         assert_eq!(ts.unwrap().len(), 999997);
     }
@@ -93,9 +95,50 @@ mod tests {
         };
 
         let before = Instant::now();
-        let ts: Result<TimeSeries<NaiveDateTime,SimpleStruct>, Box<dyn Error>>  = read_from_file("testdata/large_struct.csv",|sscdto:SimpleStructCSVDTO| TimeSeriesDataPoint::new(sscdto.timestamp,SimpleStruct{intthing:sscdto.intthing,floatvalue:sscdto.floatvalue}) ); //, "%Y-%m-%d %H:%M:%S%z"
+        let ts: Result<TimeSeries<NaiveDateTime,SimpleStruct>, TsxError>  = read_from_file("testdata/large_struct.csv",|sscdto:SimpleStructCSVDTO| TimeSeriesDataPoint::new(sscdto.timestamp,SimpleStruct{intthing:sscdto.intthing,floatvalue:sscdto.floatvalue}) ); //, "%Y-%m-%d %H:%M:%S%z"
         println!("CSV Read Elapsed time: {:.2?}", before.elapsed());
         assert_eq!(ts.unwrap().len(), 999997);
     }
+
+    fn write_tmp(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_read_missing_file() {
+        let ts: Result<TimeSeries<i64,f64>, TsxError> = read_from_file_simple("testdata/does_not_exist.csv");
+        assert!(matches!(ts, Err(TsxError::Io(ref e)) if e.kind() == std::io::ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn test_read_truncated_file() {
+        let path = write_tmp("tsxlib_csv_truncated.csv", "timestamp,value\n1,1.0\n2,2.0\n3");
+        let ts: Result<TimeSeries<i64,f64>, TsxError> = read_from_file_simple(&path);
+        assert!(matches!(ts, Err(TsxError::Csv{ row: Some(2), .. })));
+    }
+
+    #[test]
+    fn test_read_malformed_record() {
+        let path = write_tmp("tsxlib_csv_malformed.csv", "timestamp,value\n1,1.0\n2,abc\n3,3.0\n");
+        let ts: Result<TimeSeries<i64,f64>, TsxError> = read_from_file_simple(&path);
+        assert!(matches!(ts, Err(TsxError::Csv{ row: Some(1), column: Some(1), .. })));
+    }
+
+    #[test]
+    fn test_write_errors() {
+        let ts = TimeSeries::from_vecs(vec![1, 2, 3], vec![1.0, 2.0, 3.0]).unwrap();
+        let missing_dir = write_to_file_simple("testdata/does_not_exist/out.csv", &ts);
+        assert!(matches!(missing_dir, Err(TsxError::Io(_))));
+
+        let path = std::env::temp_dir().join("tsxlib_csv_unwritable.csv");
+        let unwritable = write_to_file(path.to_str().unwrap(), &ts, |dp| {
+            let mut record = std::collections::HashMap::new();
+            record.insert(dp.timestamp, dp.value);
+            record
+        });
+        assert!(matches!(unwritable, Err(TsxError::Csv{ row: Some(0), .. })));
+    }
 }
 
@@ -1,21 +1,22 @@
 //! # JSON IO
 use serde::Serialize;
 use serde::de::DeserializeOwned;
-use std::error::Error;
 use std::cmp;
 use std::hash::Hash;
 
 
 
 use crate::{data_elements::TimeSeriesDataPoint, timeseries::TimeSeries};
+use crate::error::TsxError;
 
-pub fn read_from_file<TDate,T>(file_path: &str) -> Result<TimeSeries<TDate,T>, Box<dyn Error>> 
+/// Load series from the given JSON file, if the document is malformed the error will contain the line and column where parsing failed
+pub fn read_from_file<TDate,T>(file_path: &str) -> Result<TimeSeries<TDate,T>, TsxError> 
 where 
     TDate: DeserializeOwned + 'static + Serialize + Hash + Copy + cmp::Eq + cmp::Ord, 
     T: DeserializeOwned + 'static + Copy 
 {
     let path = std::path::Path::new(file_path);
-    let file = std::fs::File::open(&path).unwrap();
+    let file = std::fs::File::open(path)?;
     let rdr = std::io::BufReader::new(file);
     let data: Vec<TimeSeriesDataPoint<TDate,T>> = serde_json::from_reader(rdr)?;
     Ok(TimeSeries::from_tsdatapoints_unchecked(data))
@@ -23,21 +24,21 @@ where
 
 pub enum JSONStyle{ Default, Pretty}
 
-pub fn write_to_file<TDate,T>(file_path: &str, ts: &TimeSeries<TDate,T>, jsonstyle: JSONStyle ) -> Result<(), Box<dyn Error>> 
+pub fn write_to_file<TDate,T>(file_path: &str, ts: &TimeSeries<TDate,T>, jsonstyle: JSONStyle ) -> Result<(), TsxError> 
 where 
     TDate: Serialize + Hash + Copy + cmp::Eq + cmp::Ord, 
     T: Serialize + Copy,
 {
     let vec: Vec<TimeSeriesDataPoint<TDate,T>> = ts.into_ordered_iter().collect();
     let path = std::path::Path::new(file_path);
-    let wtr = &std::fs::File::create(&path)?;
+    let wtr = &std::fs::File::create(path)?;
     let res = match jsonstyle {
         JSONStyle::Default => serde_json::to_writer(wtr,&vec),
         JSONStyle::Pretty => serde_json::to_writer_pretty(wtr,&vec),
     };
     match res {
         Ok(_t) => Ok(()),
-        Err(res) => Err(Box::new(res)) 
+        Err(res) => Err(TsxError::from(res)) 
     }
 }
 
@@ -63,4 +64,30 @@ mod tests {
         assert_eq!(500, ts.len());
 
     }
+
+    fn write_tmp(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_read_missing_file() {
+        let ts: Result<TimeSeries<i64,f64>, TsxError> = read_from_file("testdata/does_not_exist.json");
+        assert!(matches!(ts, Err(TsxError::Io(_))));
+    }
+
+    #[test]
+    fn test_read_truncated_file() {
+        let path = write_tmp("tsxlib_json_truncated.json", "[\n{\"timestamp\":1,\"value\":1.0},\n{\"timestamp\":2,\"val");
+        let ts: Result<TimeSeries<i64,f64>, TsxError> = read_from_file(&path);
+        assert!(matches!(ts, Err(TsxError::Json{ line: 3, .. })));
+    }
+
+    #[test]
+    fn test_read_malformed_record() {
+        let path = write_tmp("tsxlib_json_malformed.json", "[\n{\"timestamp\":1,\"value\":1.0},\n{\"timestamp\":2,\"value\":\"abc\"}\n]");
+        let ts: Result<TimeSeries<i64,f64>, TsxError> = read_from_file(&path);
+        assert!(matches!(ts, Err(TsxError::Json{ line: 3, .. })));
+    }
 }
\ No newline at end of file
@@ -1,26 +1,28 @@
 //! # Apache Parquet IO
+use parquet::errors::ParquetError;
 use parquet::file::reader::SerializedFileReader;
-use std::error::Error;
 use std::hash::Hash;
 use std::cmp;
 use serde::{Serialize};
 
 use crate::timeseries::TimeSeries;
 use crate::data_elements::TimeSeriesDataPoint;
+use crate::error::TsxError;
 
-/// Load series from the given Parquet file
-
-pub fn read_from_file<TDate: Serialize + Hash + Copy + cmp::Eq + cmp::Ord, T: Copy>(
+/// Load series from the given Parquet file. The datapoint_gen_func can return an error (i.e. from a `RowAccessor` call), in that case the error will contain the row number of the failing row
+pub fn read_from_file<TDate: Serialize + Hash + Copy + cmp::Eq + cmp::Ord, T: Copy, F: FnMut(&parquet::record::Row)->Result<TimeSeriesDataPoint<TDate,T>, ParquetError>>(
     file_path: &str,
-    datapoint_gen_func: fn(&parquet::record::Row)->TimeSeriesDataPoint<TDate,T>
-) -> Result<TimeSeries<TDate,T>, Box<dyn Error>> {
+    mut datapoint_gen_func: F
+) -> Result<TimeSeries<TDate,T>, TsxError> {
 
     let path = std::path::Path::new(file_path);
-    let file = std::fs::File::open(&path).unwrap();
-    let parquet_rdr = SerializedFileReader::new(file).unwrap();
+    let file = std::fs::File::open(path)?;
+    let parquet_rdr = SerializedFileReader::new(file)?;
     let mut data: Vec<TimeSeriesDataPoint<TDate,T>> = Vec::new();
-    for row in parquet_rdr.into_iter() {
-        let record: TimeSeriesDataPoint<TDate,T> = datapoint_gen_func(&row);
+    for (row_num, row) in parquet_rdr.into_iter().enumerate() {
+        let record: TimeSeriesDataPoint<TDate,T> = row
+            .and_then(|row| datapoint_gen_func(&row))
+            .map_err(|e| TsxError::from(e).at_row(row_num as u64))?;
         data.push(record);
     }
 
@@ -44,14 +46,14 @@ mod tests {
     fn test_read() {
         
 
-        fn datapoint_gen_func(row: &parquet::record::Row) -> TimeSeriesDataPoint<NaiveDateTime,f64> {
-            let value = row.get_double(1).unwrap();
-            let istamp = row.get_timestamp_millis(0).unwrap() as i64;
+        fn datapoint_gen_func(row: &parquet::record::Row) -> Result<TimeSeriesDataPoint<NaiveDateTime,f64>, ParquetError> {
+            let value = row.get_double(1)?;
+            let istamp = row.get_timestamp_millis(0)? as i64;
             let ts = timeutils::naive_datetime_from_millis(istamp);
-            TimeSeriesDataPoint::new(ts,value)
-        };
+            Ok(TimeSeriesDataPoint::new(ts,value))
+        }
 
-        let ts = read_from_file::<NaiveDateTime, f64>("testdata/rand_data.parquet",datapoint_gen_func).unwrap();
+        let ts = read_from_file::<NaiveDateTime, f64, _>("testdata/rand_data.parquet",datapoint_gen_func).unwrap();
 
         // println!("{:.2?}",tsrres);
         // println!("{:.2?}",ts);
@@ -60,4 +62,52 @@ mod tests {
 
     }
 
+    fn int_gen_func(row: &parquet::record::Row) -> Result<TimeSeriesDataPoint<i64,f64>, ParquetError> {
+        Ok(TimeSeriesDataPoint::new(row.get_long(0)?, row.get_double(1)?))
+    }
+
+    fn write_int_file(name: &str) -> String {
+        use parquet::data_type::Int64Type;
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+        use std::sync::Arc;
+
+        let path = std::env::temp_dir().join(name);
+        let schema = Arc::new(parse_message_type("message schema { REQUIRED INT64 stamp; REQUIRED INT64 value; }").unwrap());
+        let file = std::fs::File::create(&path).unwrap();
+        let mut writer = SerializedFileWriter::new(file, schema, Arc::new(WriterProperties::builder().build())).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        while let Some(mut col) = row_group.next_column().unwrap() {
+            col.typed::<Int64Type>().write_batch(&[1, 2, 3], None, None).unwrap();
+            col.close().unwrap();
+        }
+        row_group.close().unwrap();
+        writer.close().unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_read_missing_file() {
+        let ts = read_from_file::<i64, f64, _>("testdata/does_not_exist.parquet", int_gen_func);
+        assert!(matches!(ts, Err(TsxError::Io(_))));
+    }
+
+    #[test]
+    fn test_read_truncated_file() {
+        let path = write_int_file("tsxlib_parquet_truncated.parquet");
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        let ts = read_from_file::<i64, f64, _>(&path, int_gen_func);
+        assert!(matches!(ts, Err(TsxError::Parquet{ row: None, .. })));
+    }
+
+    #[test]
+    fn test_read_malformed_record() {
+        // the value column is an INT64 so reading it as a double fails on the first row
+        let path = write_int_file("tsxlib_parquet_malformed.parquet");
+        let ts = read_from_file::<i64, f64, _>(&path, int_gen_func);
+        assert!(matches!(ts, Err(TsxError::Parquet{ row: Some(0), .. })));
+    }
+
 }
\ No newline at end of file
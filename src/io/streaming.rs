@@ -1,5 +1,4 @@
 //! # Data Streaming Iterators
-use std::error::Error;
 use std::cmp;
 use std::fmt;
 use std::hash::Hash;
@@ -7,39 +6,42 @@ use std::io::{Read,Cursor};
 use serde::{Serialize};
 use std::sync::mpsc;
 use crate::data_elements::TimeSeriesDataPoint;
+use crate::error::TsxError;
 
 
-pub struct TimeSeriesDataPointStreamer<'a, T: Read, TDate: Hash + Copy + cmp::Eq + cmp::Ord, TDp: fmt::Display + Copy + cmp::PartialEq> {
+/// Iterator over the data points produced from the chunks read from a source, a failed read is yielded as an error and ends the stream
+pub struct TimeSeriesDataPointStreamer<'a, T: Read, TDate: Hash + Copy + cmp::Eq + cmp::Ord, TDp: fmt::Display + Copy + cmp::PartialEq, F: FnMut(&[u8]) -> TimeSeriesDataPoint<TDate,TDp>> {
     source: &'a mut T,
-    production_function: fn(&[u8]) -> TimeSeriesDataPoint<TDate,TDp>
+    production_function: F,
+    failed: bool
 }
 
 
-impl<'a, T: Read, TDate: Hash + Copy + cmp::Eq + cmp::Ord, TDp: fmt::Display + Copy + cmp::PartialEq> Iterator for TimeSeriesDataPointStreamer<'a, T,TDate,TDp> {
-    type Item = TimeSeriesDataPoint<TDate,TDp>;
+impl<'a, T: Read, TDate: Hash + Copy + cmp::Eq + cmp::Ord, TDp: fmt::Display + Copy + cmp::PartialEq, F: FnMut(&[u8]) -> TimeSeriesDataPoint<TDate,TDp>> Iterator for TimeSeriesDataPointStreamer<'a, T,TDate,TDp,F> {
+    type Item = Result<TimeSeriesDataPoint<TDate,TDp>, TsxError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
         let mut buffer = [0; 1024*1024]; //TODO ideally replace this once const generics are available
-        let res = self.source.read(&mut buffer);
-        let produce_tsdp = self.production_function;
-        match res {
-            Ok(count) => {
-            if count > 0 {
-                Some(produce_tsdp(&buffer[..count]))
-            } else {
-                None
-            }
+        match self.source.read(&mut buffer) {
+            Ok(0) => None,
+            Ok(count) => Some(Ok((self.production_function)(&buffer[..count]))),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(TsxError::Io(e)))
             },
-            Err(_e) => None,
         }
     }
 }
 
-impl<'a, T: Read, TDate: Hash + Copy + cmp::Eq + cmp::Ord, TDp: fmt::Display + Copy + cmp::PartialEq> TimeSeriesDataPointStreamer<'a, T,TDate,TDp>{
-    pub fn new(source: &'a mut T, production_function: fn(&[u8])->TimeSeriesDataPoint<TDate,TDp>) -> TimeSeriesDataPointStreamer<'a, T,TDate,TDp>{
+impl<'a, T: Read, TDate: Hash + Copy + cmp::Eq + cmp::Ord, TDp: fmt::Display + Copy + cmp::PartialEq, F: FnMut(&[u8]) -> TimeSeriesDataPoint<TDate,TDp>> TimeSeriesDataPointStreamer<'a, T,TDate,TDp,F>{
+    pub fn new(source: &'a mut T, production_function: F) -> TimeSeriesDataPointStreamer<'a, T,TDate,TDp,F>{
         TimeSeriesDataPointStreamer {
             source,
             production_function,
+            failed: false,
         }
     }
 }
@@ -71,36 +73,23 @@ impl<'a, TDate: Hash + Copy + cmp::Eq + cmp::Ord, TDp: fmt::Display + Copy + cmp
 
 
 
-type ProdResult = std::result::Result<std::vec::Vec<u8>, Box<dyn Error>>;
-pub struct TimeSeriesBytesStreamer<'a, TDate: Hash + Copy + cmp::Eq + cmp::Ord + Serialize, T: fmt::Display + Copy + cmp::PartialEq + Serialize>{    
+type ProdResult = std::result::Result<std::vec::Vec<u8>, TsxError>;
+/// Reader over the serialized data points of an iterator, an error from the production function is passed on as the io error (or as the inner error of one)
+pub struct TimeSeriesBytesStreamer<'a, TDate: Hash + Copy + cmp::Eq + cmp::Ord + Serialize, T: fmt::Display + Copy + cmp::PartialEq + Serialize, F: FnMut(&TimeSeriesDataPoint<TDate,T>) -> ProdResult>{    
 
     source: &'a mut dyn Iterator<Item=TimeSeriesDataPoint<TDate,T>>,
-    production_function: fn(&TimeSeriesDataPoint<TDate,T>) -> ProdResult
+    production_function: F
 }
 
-impl <'a, TDate: Hash + Copy + cmp::Eq + cmp::Ord + Serialize, T: fmt::Display + Copy + cmp::PartialEq + Serialize> Read for TimeSeriesBytesStreamer<'a,TDate,T> {
+impl <'a, TDate: Hash + Copy + cmp::Eq + cmp::Ord + Serialize, T: fmt::Display + Copy + cmp::PartialEq + Serialize, F: FnMut(&TimeSeriesDataPoint<TDate,T>) -> ProdResult> Read for TimeSeriesBytesStreamer<'a,TDate,T,F> {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error>{
-        if let Some(dp) = self.source.next() {
-            let func = self.production_function;
-            let res = func(&dp);
-
-            if let Ok(bytes) = res{
-                let len = bytes.len();
-                let mut file = Cursor::new(bytes);
-                let _ = file.read(buf);
-                Ok(len)
-            }
-            else{
-                Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "out of data",))
-            }
-        }
-        else{
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "out of data",
-            ))
+        match self.source.next() {
+            Some(dp) => match (self.production_function)(&dp) {
+                Ok(bytes) => Cursor::new(bytes).read(buf),
+                Err(TsxError::Io(e)) => Err(e),
+                Err(e) => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            },
+            None => Ok(0),
         }
     }   
 }
@@ -133,13 +122,13 @@ mod tests {
 
         let mut channel_reciever = receiver.iter();
 
-        fn prod_func(x: &TimeSeriesDataPoint<NaiveDateTime,f64>) -> Result<Vec<u8>,Box<dyn Error>> {
+        fn prod_func(x: &TimeSeriesDataPoint<NaiveDateTime,f64>) -> Result<Vec<u8>,TsxError> {
             let now = std::time::Instant::now();
             println!("{:.2?}",now.elapsed());
             let ser = bincode::serialize(x);
             match ser {
                 Ok(ser) => Ok(ser),
-                Err(e) => Err(e)                
+                Err(e) => Err(TsxError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
             }
         }
 
@@ -149,12 +138,56 @@ mod tests {
             bincode::deserialize::<TimeSeriesDataPoint<NaiveDateTime,f64>>(x).unwrap()
         }
         
-        let consumer = TimeSeriesDataPointStreamer{source:&mut streamer, production_function: gen_dp};
-        let res: TimeSeries<NaiveDateTime,f64> = consumer.collect();
+        let consumer = TimeSeriesDataPointStreamer::new(&mut streamer, gen_dp);
+        let res: TimeSeries<NaiveDateTime,f64> = consumer.collect::<Result<_,_>>().unwrap();
         println!("{:.2?}",res);
         assert_eq!(res, tscopy);
     }
     #[test]
+    fn test_producer_error() {
+        let values = vec![1.0, 2.0];
+        let index = (0..values.len()).map(|i| NaiveDateTime::from_timestamp(60 * i as i64,0)).collect();
+        let ts = TimeSeries::from_vecs(index, values).unwrap();
+        let mut buf = [0; 64];
+
+        let mut iter = ts.into_ordered_iter();
+        let mut streamer = TimeSeriesBytesStreamer{source: &mut iter, production_function: |_x: &TimeSeriesDataPoint<NaiveDateTime,f64>| Err(TsxError::LengthMismatch{index: 1, values: 2})};
+        let err = streamer.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(matches!(err.get_ref().and_then(|e| e.downcast_ref::<TsxError>()), Some(TsxError::LengthMismatch{index: 1, values: 2})));
+
+        let mut iter = ts.into_ordered_iter();
+        let mut streamer = TimeSeriesBytesStreamer{source: &mut iter, production_function: |_x: &TimeSeriesDataPoint<NaiveDateTime,f64>| Err(TsxError::Io(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "gone")))};
+        assert_eq!(streamer.read(&mut buf).unwrap_err().kind(), std::io::ErrorKind::BrokenPipe);
+
+        let mut iter = std::iter::empty();
+        let mut streamer = TimeSeriesBytesStreamer{source: &mut iter, production_function: |_x: &TimeSeriesDataPoint<NaiveDateTime,f64>| Ok(vec![1])};
+        assert_eq!(streamer.read(&mut buf).unwrap(), 0);
+    }
+    #[test]
+    fn test_streamer_read_error() {
+        struct FailingRead { reads: usize }
+        impl Read for FailingRead {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.reads += 1;
+                if self.reads > 1 {
+                    return Err(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset"));
+                }
+                buf[0] = 7;
+                Ok(1)
+            }
+        }
+        let mut source = FailingRead{ reads: 0 };
+        let mut consumer = TimeSeriesDataPointStreamer::new(&mut source, |x: &[u8]| TimeSeriesDataPoint::new(x[0] as i64, 1.0));
+        assert_eq!(consumer.next().unwrap().unwrap(), TimeSeriesDataPoint::new(7, 1.0));
+        assert!(matches!(consumer.next(), Some(Err(TsxError::Io(ref e))) if e.kind() == std::io::ErrorKind::ConnectionReset));
+        assert!(consumer.next().is_none());
+
+        let mut source = FailingRead{ reads: 0 };
+        let res: Result<TimeSeries<i64,f64>, TsxError> = TimeSeriesDataPointStreamer::new(&mut source, |x: &[u8]| TimeSeriesDataPoint::new(x[0] as i64, 1.0)).collect();
+        assert!(matches!(res, Err(TsxError::Io(_))));
+    }
+    #[test]
     fn test_reciever_consumer() {
         let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
         let index = (0..values.len()).map(|i| NaiveDateTime::from_timestamp(60 * i as i64,0)).collect();
@@ -6,6 +6,7 @@ use std::collections::{HashMap};
 use serde::{Serialize};
 use std::convert::TryInto;
 use crate::index::{HashableIndex};
+use crate::algo::time_distance::AsofCompareFunc;
 
 /// JoinEngine<TIndex> is responsible for join logic, it consists of two indicies. `idx_this` is the LHS and `idx_other` is the RHS. Indicies are passed by reference
 pub struct JoinEngine<'a, TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> {
@@ -24,6 +25,31 @@ pub struct IndexJoinPotentiallyUnmatchedPair{
     pub other_idx: Option<usize> 
 }
 
+/// Represents an potentially unmatched set of indicies for a right join, the other side is always matched
+pub struct IndexJoinPotentiallyUnmatchedRightPair{
+    pub this_idx: Option<usize>,
+    pub other_idx: usize 
+}
+
+/// Represents a set of indicies where either side can be unmatched, at least one side is always set
+pub struct IndexJoinOptionalPair{
+    pub this_idx: Option<usize>,
+    pub other_idx: Option<usize> 
+}
+
+/// Represents the elements of the other index that bracket an element of this index, `prior_idx` is the last element not larger than this and `next_idx` the first element not smaller than this.
+/// On an exact match both point at the same element
+pub struct IndexJoinBracket{
+    pub this_idx: usize,
+    pub prior_idx: Option<usize>,
+    pub next_idx: Option<usize>
+}
+
+/// JoinStrategy picks the algorithm used to join two indicies. `Auto` picks based on the relative size of the indicies, 
+/// if one side is much smaller it is probed into the larger side via binary search, otherwise a merge join is used
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum JoinStrategy{ Merge, Hash, Auto}
+
 pub fn prior_func(idx: usize) -> usize{
     if idx == 0 { 
         0 
@@ -44,7 +70,7 @@ impl <'a, TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> JoinEngine<'a,
 
     #[cfg(feature = "hash_precompare")]
     fn hash_index(&self, index: &HashableIndex<TIndex>) -> u64{ 
-        let bytes = bincode::serialize(&index.values).unwrap();
+        let bytes = bincode::serialize(index.values()).unwrap();
         seahash::hash(&bytes)
     }
 
@@ -81,7 +107,7 @@ impl <'a, TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> JoinEngine<'a,
             self.idx_this.iter().enumerate().map(|(idx,_x)| IndexJoinPair{this_idx : idx,other_idx : idx}).collect()
         }
         else {
-            let (lookup, this_shorter) = match self.idx_this.len() <= self.idx_this.len() { 
+            let (lookup, this_shorter) = match self.idx_this.len() <= self.idx_other.len() { 
                 true => (self.gen_base_lookup(&self.idx_this),false),
                 false => (self.gen_base_lookup(&self.idx_other),true)
             };
@@ -162,24 +188,23 @@ impl <'a, TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> JoinEngine<'a,
             self.idx_this.iter().enumerate().map(|(idx,_x)| IndexJoinPotentiallyUnmatchedPair{this_idx : idx,other_idx : Some(idx)}).collect()
         }
         else{
-            let mut output: Vec<IndexJoinPotentiallyUnmatchedPair> = Vec::new();
+            let mut output: Vec<IndexJoinPotentiallyUnmatchedPair> = Vec::with_capacity(self.idx_this.len());
             let mut pos1: usize = 0;
             let mut pos2: usize = 0;
 
             while pos1 < self.idx_this.len() {
+                if pos2 == self.idx_other.len() {
+                    //the first index might still be longer so we gotta keep rolling it forward even though we are out of space on the other index
+                    output.push(IndexJoinPotentiallyUnmatchedPair{
+                        this_idx: pos1,
+                        other_idx: None
+                    });
+                    pos1 += 1;
+                    continue;
+                }
                 match self.idx_this[pos1].cmp(&self.idx_other[pos2]) {
                     cmp::Ordering::Greater => {
-                        output.push(IndexJoinPotentiallyUnmatchedPair{
-                            this_idx: pos1,
-                            other_idx: None
-                        });
-                        if pos2 < (self.idx_other.len() - 1){
-                            pos2 += 1;
-                        }
-                        else{
-                            pos1 += 1;     //the first index might still be longer so we gotta keep rolling it forward even though we are out of space on the other index                       
-                        }
-
+                        pos2 += 1;
                     },
                     cmp::Ordering::Less => {
                         output.push(IndexJoinPotentiallyUnmatchedPair{
@@ -194,10 +219,88 @@ impl <'a, TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> JoinEngine<'a,
                             other_idx: Some(pos2)
                         });
                         pos1 += 1;
-                        if pos2 < (self.idx_other.len() - 1){
+                        pos2 += 1;
+                    }
+                }
+            }
+            output
+        }
+    }
+
+    /// Hash outer join. 
+    /// Pairs are returned in index order, keys that only exist in one of the indicies have None on the other side
+    pub fn get_outer_hash_joined_indicies(&self) -> Vec<IndexJoinOptionalPair>
+    {
+        if self.index_is_same() {
+            //if we are the same just skip this whole thing
+            self.idx_this.iter().enumerate().map(|(idx,_x)| IndexJoinOptionalPair{this_idx : Some(idx),other_idx : Some(idx)}).collect()
+        }
+        else{
+            let lookup = self.gen_base_lookup(self.idx_other);
+            let mut matched_other = vec![false; self.idx_other.len()];
+
+            let mut res: Vec<IndexJoinOptionalPair> = self.idx_this.iter().enumerate().map(|(idx_this, key)| {
+                let other_idx = lookup.get(key).copied();
+                if let Some(idx_other) = other_idx {
+                    matched_other[idx_other] = true;
+                }
+                IndexJoinOptionalPair { 
+                    this_idx : Some(idx_this), 
+                    other_idx
+                }
+            })
+            .collect();
+
+            res.extend(matched_other.iter().enumerate()
+                .filter(|(_idx_other, matched)| !**matched)
+                .map(|(idx_other, _matched)| IndexJoinOptionalPair { this_idx : None, other_idx : Some(idx_other) }));
+
+            res.sort_by(|a, b| self.outer_pair_key(a).cmp(self.outer_pair_key(b)));
+            res
+        }
+    }
+
+    fn outer_pair_key(&self, pair: &IndexJoinOptionalPair) -> &TIndex {
+        match pair.this_idx {
+            Some(idx) => &self.idx_this[idx],
+            None => &self.idx_other[pair.other_idx.unwrap()] // pairs always have at least one side set
+        }
+    }
+
+    /// Outer Merge Join, the union of both indicies. Keys that only exist in one of the indicies have None on the other side
+    pub fn get_outer_merge_joined_indicies(&self) -> Vec<IndexJoinOptionalPair>
+    {
+        if self.index_is_same() {
+            //if we are the same just skip this whole thing
+            self.idx_this.iter().enumerate().map(|(idx,_x)| IndexJoinOptionalPair{this_idx : Some(idx),other_idx : Some(idx)}).collect()
+        }
+        else{
+            let mut output: Vec<IndexJoinOptionalPair> = Vec::with_capacity(cmp::max(self.idx_this.len(), self.idx_other.len()));
+            let mut pos1: usize = 0;
+            let mut pos2: usize = 0;
+
+            while pos1 < self.idx_this.len() || pos2 < self.idx_other.len() {
+                if pos1 == self.idx_this.len() {
+                    output.push(IndexJoinOptionalPair{ this_idx: None, other_idx: Some(pos2) });
+                    pos2 += 1;
+                } else if pos2 == self.idx_other.len() {
+                    output.push(IndexJoinOptionalPair{ this_idx: Some(pos1), other_idx: None });
+                    pos1 += 1;
+                } else {
+                    match self.idx_this[pos1].cmp(&self.idx_other[pos2]) {
+                        cmp::Ordering::Greater => {
+                            output.push(IndexJoinOptionalPair{ this_idx: None, other_idx: Some(pos2) });
+                            pos2 += 1;
+                        },
+                        cmp::Ordering::Less => {
+                            output.push(IndexJoinOptionalPair{ this_idx: Some(pos1), other_idx: None });
+                            pos1 += 1;
+                        },
+                        cmp::Ordering::Equal => {
+                            output.push(IndexJoinOptionalPair{ this_idx: Some(pos1), other_idx: Some(pos2) });
+                            pos1 += 1;
                             pos2 += 1;
                         }
-                        
                     }
                 }
             }
@@ -205,6 +308,200 @@ impl <'a, TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> JoinEngine<'a,
         }
     }
 
+    /// Right Merge Join. 
+    /// All right values are joined so no Option<usize> on the other index
+    pub fn get_right_merge_joined_indicies(&self) -> Vec<IndexJoinPotentiallyUnmatchedRightPair>
+    {
+        if self.index_is_same() {
+            //if we are the same just skip this whole thing
+            self.idx_other.iter().enumerate().map(|(idx,_x)| IndexJoinPotentiallyUnmatchedRightPair{this_idx : Some(idx),other_idx : idx}).collect()
+        }
+        else{
+            let mut output: Vec<IndexJoinPotentiallyUnmatchedRightPair> = Vec::with_capacity(self.idx_other.len());
+            let mut pos1: usize = 0;
+            let mut pos2: usize = 0;
+
+            while pos2 < self.idx_other.len() {
+                if pos1 == self.idx_this.len() {
+                    output.push(IndexJoinPotentiallyUnmatchedRightPair{ this_idx: None, other_idx: pos2 });
+                    pos2 += 1;
+                    continue;
+                }
+                match self.idx_this[pos1].cmp(&self.idx_other[pos2]) {
+                    cmp::Ordering::Greater => {
+                        output.push(IndexJoinPotentiallyUnmatchedRightPair{ this_idx: None, other_idx: pos2 });
+                        pos2 += 1;
+                    },
+                    cmp::Ordering::Less => {
+                        pos1 += 1;
+                    },
+                    cmp::Ordering::Equal => {
+                        output.push(IndexJoinPotentiallyUnmatchedRightPair{ this_idx: Some(pos1), other_idx: pos2 });
+                        pos1 += 1;
+                        pos2 += 1;
+                    }
+                }
+            }
+            output
+        }
+    }
+
+    /// Semi Merge Join, returns the positions in `idx_this` that have a match in `idx_other`
+    pub fn get_semi_merge_joined_indicies(&self) -> Vec<usize>
+    {
+        if self.index_is_same() {
+            //if we are the same just skip this whole thing
+            (0..self.idx_this.len()).collect()
+        }
+        else {
+            let mut output: Vec<usize> = Vec::new();
+            let mut pos1: usize = 0;
+            let mut pos2: usize = 0;
+
+            while pos1 < self.idx_this.len() && pos2 < self.idx_other.len() {
+                match self.idx_this[pos1].cmp(&self.idx_other[pos2]) {
+                    cmp::Ordering::Greater => {
+                        pos2 += 1;
+                    },
+                    cmp::Ordering::Less => {
+                        pos1 += 1;
+                    },
+                    cmp::Ordering::Equal => {
+                        output.push(pos1);
+                        pos1 += 1;
+                        pos2 += 1;
+                    }
+                }
+            }
+            output
+        }
+    }
+
+    /// Anti Merge Join, returns the positions in `idx_this` that do not have a match in `idx_other`
+    pub fn get_anti_merge_joined_indicies(&self) -> Vec<usize>
+    {
+        if self.index_is_same() {
+            //if we are the same there is nothing left over
+            Vec::new()
+        }
+        else {
+            let mut output: Vec<usize> = Vec::new();
+            let mut pos1: usize = 0;
+            let mut pos2: usize = 0;
+
+            while pos1 < self.idx_this.len() {
+                if pos2 == self.idx_other.len() {
+                    output.push(pos1);
+                    pos1 += 1;
+                    continue;
+                }
+                match self.idx_this[pos1].cmp(&self.idx_other[pos2]) {
+                    cmp::Ordering::Greater => {
+                        pos2 += 1;
+                    },
+                    cmp::Ordering::Less => {
+                        output.push(pos1);
+                        pos1 += 1;
+                    },
+                    cmp::Ordering::Equal => {
+                        pos1 += 1;
+                        pos2 += 1;
+                    }
+                }
+            }
+            output
+        }
+    }
+
+    /// a probe of `small` keys into `large` via binary search costs roughly small*log2(large) comparisons vs small+large for a merge
+    fn probe_is_cheaper(small: usize, large: usize) -> bool {
+        let log_large = (usize::BITS - large.leading_zeros()) as usize;
+        small.saturating_mul(log_large) < small + large
+    }
+
+    /// Binary search join, the shorter index is probed into the longer one. Each search starts from the last match so the cost is O(s*log(l))
+    pub fn get_inner_probe_joined_indicies(&self) -> Vec<IndexJoinPair>
+    {
+        let mut output: Vec<IndexJoinPair> = Vec::new();
+        let mut lo: usize = 0;
+        if self.idx_this.len() <= self.idx_other.len() {
+            for (idx_this, key) in self.idx_this.iter().enumerate() {
+                match self.idx_other.values()[lo..].binary_search(key) {
+                    Ok(pos) => {
+                        output.push(IndexJoinPair{ this_idx: idx_this, other_idx: lo + pos });
+                        lo += pos + 1;
+                    },
+                    Err(pos) => lo += pos
+                }
+            }
+        } else {
+            for (idx_other, key) in self.idx_other.iter().enumerate() {
+                match self.idx_this.values()[lo..].binary_search(key) {
+                    Ok(pos) => {
+                        output.push(IndexJoinPair{ this_idx: lo + pos, other_idx: idx_other });
+                        lo += pos + 1;
+                    },
+                    Err(pos) => lo += pos
+                }
+            }
+        }
+        output
+    }
+
+    /// Binary search left join, every key of `idx_this` is probed into `idx_other`. Each search starts from the last match so the cost is O(t*log(o))
+    pub fn get_left_probe_joined_indicies(&self) -> Vec<IndexJoinPotentiallyUnmatchedPair>
+    {
+        let mut lo: usize = 0;
+        self.idx_this.iter().enumerate().map(|(idx_this, key)| {
+            match self.idx_other.values()[lo..].binary_search(key) {
+                Ok(pos) => {
+                    let other_idx = lo + pos;
+                    lo = other_idx + 1;
+                    IndexJoinPotentiallyUnmatchedPair{ this_idx: idx_this, other_idx: Some(other_idx) }
+                },
+                Err(pos) => {
+                    lo += pos;
+                    IndexJoinPotentiallyUnmatchedPair{ this_idx: idx_this, other_idx: None }
+                }
+            }
+        })
+        .collect()
+    }
+
+    /// Inner join using the given strategy
+    pub fn get_inner_joined_indicies(&self, strategy: JoinStrategy) -> Vec<IndexJoinPair>
+    {
+        match strategy {
+            JoinStrategy::Merge => self.get_inner_merge_joined_indicies(),
+            JoinStrategy::Hash => self.get_inner_hash_joined_indicies(),
+            JoinStrategy::Auto => {
+                let small = cmp::min(self.idx_this.len(), self.idx_other.len());
+                let large = cmp::max(self.idx_this.len(), self.idx_other.len());
+                if !self.index_is_same() && Self::probe_is_cheaper(small, large) {
+                    self.get_inner_probe_joined_indicies()
+                } else {
+                    self.get_inner_merge_joined_indicies()
+                }
+            }
+        }
+    }
+
+    /// Left join using the given strategy. Since every left key is in the output, `Auto` will only probe when the left index is the short side
+    pub fn get_left_joined_indicies(&self, strategy: JoinStrategy) -> Vec<IndexJoinPotentiallyUnmatchedPair>
+    {
+        match strategy {
+            JoinStrategy::Merge => self.get_left_merge_joined_indicies(),
+            JoinStrategy::Hash => self.get_left_hash_joined_indicies(),
+            JoinStrategy::Auto => {
+                if !self.index_is_same() && Self::probe_is_cheaper(self.idx_this.len(), self.idx_other.len()) {
+                    self.get_left_probe_joined_indicies()
+                } else {
+                    self.get_left_merge_joined_indicies()
+                }
+            }
+        }
+    }
+
     /// merge sort join join a and b.
     pub fn get_inner_merge_joined_indicies(&self) -> Vec<IndexJoinPair>
     {
@@ -239,16 +536,52 @@ impl <'a, TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> JoinEngine<'a,
         }
     }
     
+    /// Bracketing Merge Join, for every element of `idx_this` finds the elements of `idx_other` right before and after it (or equal to it). This is what reindexing and filling is built on
+    pub fn get_bracketing_merge_joined_indicies(&self) -> Vec<IndexJoinBracket>
+    {
+        let mut output: Vec<IndexJoinBracket> = Vec::with_capacity(self.idx_this.len());
+        let otherlen = self.idx_other.len();
+        let mut pos2: usize = 0;
+        for pos1 in 0..self.idx_this.len() {
+            // pos2 is the first element of the other index that is not smaller than this, it is otherlen if there is no such element
+            while pos2 < otherlen && self.idx_other[pos2] < self.idx_this[pos1] {
+                pos2 += 1;
+            }
+            let next_idx = match pos2 < otherlen {
+                true => Some(pos2),
+                false => None
+            };
+            let prior_idx = match next_idx {
+                Some(idx) if self.idx_other[idx] == self.idx_this[pos1] => Some(idx),
+                _ => pos2.checked_sub(1)
+            };
+            output.push(IndexJoinBracket{ this_idx: pos1, prior_idx, next_idx });
+        }
+        output
+    }
+
     /// as of join. this is a variation of merge join that allows for indicies to be equal based on a custom comperator func
-    pub fn get_asof_merge_joined_indicies(&self, compare_func: Option<Box<dyn Fn(&TIndex,&TIndex,&TIndex)->(cmp::Ordering,i64)>>,other_idx_func: Option<Box<dyn Fn(usize)->usize>>) -> Vec<IndexJoinPotentiallyUnmatchedPair>
-    { #![allow(clippy::type_complexity)]
-        if self.index_is_same() {
+    ///
+    /// For every element of `idx_this` the comperator is called with (this, other, candidate) where other is the first element of `idx_other` that is not smaller than this (the last element if there is none)
+    /// and candidate is the element at `other_idx_func(position of other)`. When the comperator returns `Equal` the returned offset is added to the position of other to get the match
+    pub fn get_asof_merge_joined_indicies(&self, compare_func: Option<AsofCompareFunc<TIndex>>,other_idx_func: Option<Box<dyn Fn(usize)->usize>>) -> Vec<IndexJoinPotentiallyUnmatchedPair>
+    {
+        self.get_asof_merge_joined_indicies_allow_exact(compare_func, other_idx_func, true)
+    }
+
+    /// Same as `get_asof_merge_joined_indicies` but if `allow_exact_matches` is false, an element of `idx_other` that is equal to this is skipped as if it was not in the index
+    pub fn get_asof_merge_joined_indicies_allow_exact(&self, compare_func: Option<AsofCompareFunc<TIndex>>,other_idx_func: Option<Box<dyn Fn(usize)->usize>>, allow_exact_matches: bool) -> Vec<IndexJoinPotentiallyUnmatchedPair>
+    {
+        if allow_exact_matches && self.index_is_same() {
             //if we are the same just skip this whole thing
             self.idx_this.iter().enumerate().map(|(idx,_x)| IndexJoinPotentiallyUnmatchedPair{this_idx : idx,other_idx : Some(idx)}).collect()
         }
         else {
-            let mut output: Vec<IndexJoinPotentiallyUnmatchedPair> = Vec::new();
-            let mut pos1: usize = 0;
+            let mut output: Vec<IndexJoinPotentiallyUnmatchedPair> = Vec::with_capacity(self.idx_this.len());
+            let otherlen = self.idx_other.len();
+            if otherlen == 0 {
+                return self.idx_this.iter().enumerate().map(|(idx,_x)| IndexJoinPotentiallyUnmatchedPair{this_idx : idx,other_idx : None}).collect();
+            }
             let mut pos2: usize = 0;
 
             let comp_func = match compare_func{
@@ -261,41 +594,44 @@ impl <'a, TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> JoinEngine<'a,
                 None => Box::new(|idx| idx)
             };
 
-            while pos1 < self.idx_this.len() {
-                let comp_res = comp_func(&self.idx_this[pos1],&self.idx_other[pos2],&self.idx_other[cand_idx_func(pos2)]);
-                let offset = comp_res.1;
-                match comp_res.0 { 
+            for pos1 in 0..self.idx_this.len() {
+                // pos2 is the first element of the other index that is not smaller than this, it is otherlen if there is no such element
+                while pos2 < otherlen && self.idx_other[pos2] < self.idx_this[pos1] {
+                    pos2 += 1;
+                }
+                // if exact matches are not allowed we look at the other index as if the element equal to this was not there
+                let skipped = match !allow_exact_matches && pos2 < otherlen && self.idx_other[pos2] == self.idx_this[pos1] {
+                    true => Some(pos2),
+                    false => None
+                };
+                let view_len = otherlen - usize::from(skipped.is_some());
+                if view_len == 0 {
+                    output.push(IndexJoinPotentiallyUnmatchedPair{ this_idx: pos1, other_idx: None });
+                    continue;
+                }
+                let to_other_idx = |idx: usize| match skipped {
+                    Some(skip) if idx >= skip => idx + 1,
+                    _ => idx
+                };
+                let other_pos = to_other_idx(cmp::min(pos2, view_len - 1));
+                let cand_pos = to_other_idx(cmp::min(cand_idx_func(pos2), view_len - 1));
+                let comp_res = comp_func(&self.idx_this[pos1],&self.idx_other[other_pos],&self.idx_other[cand_pos]);
+                let other_idx = match comp_res.0 { 
                     // (Evaluated as,  but is actually)
-                    cmp::Ordering::Greater => {
-                        output.push(IndexJoinPotentiallyUnmatchedPair{
-                            this_idx: pos1,
-                            other_idx: None
-                        });
-                        if pos2 < (self.idx_other.len() -  1){
-                            pos1 += 1; //the first index might still be longer so we gotta keep rolling it forward even though we are out of space on the other index
-                        }
-
-                    },
-                    cmp::Ordering::Less => {
-                        output.push(IndexJoinPotentiallyUnmatchedPair{
-                            this_idx: pos1,
-                            other_idx: None
-                        });
-                        pos1 += 1;
-                    },
+                    cmp::Ordering::Greater | cmp::Ordering::Less => None,
                     cmp::Ordering::Equal => {
                         let pas64:i64 = pos2.try_into().unwrap();
-                        let idx0:i64 =  pas64 + offset;
-                        output.push(IndexJoinPotentiallyUnmatchedPair{
-                            this_idx: pos1,
-                            other_idx: Some(idx0.try_into().unwrap())
-                        });
-                        if self.idx_this[pos1].eq(&self.idx_other[pos2]) && pos2 < (self.idx_other.len() - 1) { // only incr if things are actually equal and you have room to run
-                            pos2 += 1;
+                        let idx0:i64 =  pas64 + comp_res.1;
+                        match idx0 >= 0 && idx0 < view_len as i64 {
+                            true => Some(to_other_idx(idx0 as usize)),
+                            false => None
                         }
-                        pos1 += 1;
                     }
-                }
+                };
+                output.push(IndexJoinPotentiallyUnmatchedPair{
+                    this_idx: pos1,
+                    other_idx
+                });
             }
             output
         }
@@ -305,3 +641,168 @@ impl <'a, TIndex: Serialize + Hash + Clone + cmp::Eq + cmp::Ord> JoinEngine<'a,
 }
 
 
+/// -----------------------------------------------------------------------------------------------------------------------------------------
+/// Unit Test Area
+/// -----------------------------------------------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outer_pairs(this: &HashableIndex<i32>, other: &HashableIndex<i32>, use_hash: bool) -> Vec<(Option<usize>,Option<usize>)> {
+        let je = JoinEngine{idx_this : this, idx_other : other};
+        let res = match use_hash {
+            true => je.get_outer_hash_joined_indicies(),
+            false => je.get_outer_merge_joined_indicies()
+        };
+        res.iter().map(|x| (x.this_idx, x.other_idx)).collect()
+    }
+
+    #[test]
+    fn test_bracketing_join() {
+        let this = HashableIndex::new(vec![0, 2, 3, 6, 9]);
+        let other = HashableIndex::new(vec![1, 3, 5]);
+        let je = JoinEngine{idx_this : &this, idx_other : &other};
+        let res: Vec<(Option<usize>,Option<usize>)> = je.get_bracketing_merge_joined_indicies().iter().map(|x| (x.prior_idx, x.next_idx)).collect();
+        assert_eq!(res, vec![(None, Some(0)), (Some(0), Some(1)), (Some(1), Some(1)), (Some(2), None), (Some(2), None)]);
+
+        let empty = HashableIndex::new(vec![]);
+        let je = JoinEngine{idx_this : &this, idx_other : &empty};
+        assert!(je.get_bracketing_merge_joined_indicies().iter().all(|x| x.prior_idx.is_none() && x.next_idx.is_none()));
+    }
+
+    #[test]
+    fn test_outer_join_disjoint() {
+        let this = HashableIndex::new(vec![1, 3, 5]);
+        let other = HashableIndex::new(vec![2, 4]);
+        let expected = vec![(Some(0), None), (None, Some(0)), (Some(1), None), (None, Some(1)), (Some(2), None)];
+        assert_eq!(outer_pairs(&this, &other, false), expected);
+        assert_eq!(outer_pairs(&this, &other, true), expected);
+    }
+
+    #[test]
+    fn test_outer_join_overlapping() {
+        let this = HashableIndex::new(vec![1, 2, 3, 4]);
+        let other = HashableIndex::new(vec![3, 4, 5, 6]);
+        let expected = vec![(Some(0), None), (Some(1), None), (Some(2), Some(0)), (Some(3), Some(1)), (None, Some(2)), (None, Some(3))];
+        assert_eq!(outer_pairs(&this, &other, false), expected);
+        assert_eq!(outer_pairs(&this, &other, true), expected);
+    }
+
+    #[test]
+    fn test_outer_join_identical() {
+        let this = HashableIndex::new(vec![1, 2, 3]);
+        let expected = vec![(Some(0), Some(0)), (Some(1), Some(1)), (Some(2), Some(2))];
+        assert_eq!(outer_pairs(&this, &this, false), expected);
+        assert_eq!(outer_pairs(&this, &this, true), expected);
+    }
+
+    #[test]
+    fn test_outer_join_empty() {
+        let this = HashableIndex::new(vec![1, 2]);
+        let other = HashableIndex::new(vec![]);
+        assert_eq!(outer_pairs(&this, &other, false), vec![(Some(0), None), (Some(1), None)]);
+        assert_eq!(outer_pairs(&other, &this, true), vec![(None, Some(0)), (None, Some(1))]);
+    }
+
+    #[test]
+    fn test_right_join() {
+        let this = HashableIndex::new(vec![1, 2, 4, 6]);
+        let other = HashableIndex::new(vec![0, 2, 3, 4, 7, 8]);
+        let je = JoinEngine{idx_this : &this, idx_other : &other};
+        let res: Vec<(Option<usize>,usize)> = je.get_right_merge_joined_indicies().iter().map(|x| (x.this_idx, x.other_idx)).collect();
+        assert_eq!(res, vec![(None, 0), (Some(1), 1), (None, 2), (Some(2), 3), (None, 4), (None, 5)]);
+
+        let empty = HashableIndex::new(vec![]);
+        let je = JoinEngine{idx_this : &empty, idx_other : &this};
+        let res: Vec<(Option<usize>,usize)> = je.get_right_merge_joined_indicies().iter().map(|x| (x.this_idx, x.other_idx)).collect();
+        assert_eq!(res, vec![(None, 0), (None, 1), (None, 2), (None, 3)]);
+    }
+
+    #[test]
+    fn test_semi_and_anti_join() {
+        let this = HashableIndex::new(vec![1, 2, 4, 6, 9]);
+        let other = HashableIndex::new(vec![0, 2, 3, 4, 7]);
+        let je = JoinEngine{idx_this : &this, idx_other : &other};
+        assert_eq!(je.get_semi_merge_joined_indicies(), vec![1, 2]);
+        assert_eq!(je.get_anti_merge_joined_indicies(), vec![0, 3, 4]);
+
+        let je = JoinEngine{idx_this : &this, idx_other : &this};
+        assert_eq!(je.get_semi_merge_joined_indicies(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(je.get_anti_merge_joined_indicies(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_left_merge_join_empty_other() {
+        let this = HashableIndex::new(vec![1, 2, 3]);
+        let empty = HashableIndex::new(vec![]);
+        let je = JoinEngine{idx_this : &this, idx_other : &empty};
+        let res: Vec<(usize,Option<usize>)> = je.get_left_merge_joined_indicies().iter().map(|x| (x.this_idx, x.other_idx)).collect();
+        assert_eq!(res, vec![(0, None), (1, None), (2, None)]);
+    }
+
+    #[test]
+    fn test_left_merge_join_one_row_per_left_key() {
+        let this = HashableIndex::new(vec![5, 10, 20, 30]);
+        let other = HashableIndex::new(vec![1, 2, 10, 15, 16, 30]);
+        let je = JoinEngine{idx_this : &this, idx_other : &other};
+        let res: Vec<(usize,Option<usize>)> = je.get_left_merge_joined_indicies().iter().map(|x| (x.this_idx, x.other_idx)).collect();
+        assert_eq!(res, vec![(0, None), (1, Some(2)), (2, None), (3, Some(5))]);
+        let hash: Vec<(usize,Option<usize>)> = je.get_left_hash_joined_indicies().iter().map(|x| (x.this_idx, x.other_idx)).collect();
+        assert_eq!(res, hash);
+    }
+
+    #[test]
+    fn test_join_strategies() {
+        let large = HashableIndex::new((0..1000).collect::<Vec<i32>>());
+        let small = HashableIndex::new(vec![-5, 3, 500, 501, 998, 1200]);
+        let pairs = |je: &JoinEngine<i32>, strategy: JoinStrategy| -> Vec<(usize,usize)> {
+            je.get_inner_joined_indicies(strategy).iter().map(|x| (x.this_idx, x.other_idx)).collect()
+        };
+        let left_pairs = |je: &JoinEngine<i32>, strategy: JoinStrategy| -> Vec<(usize,Option<usize>)> {
+            je.get_left_joined_indicies(strategy).iter().map(|x| (x.this_idx, x.other_idx)).collect()
+        };
+
+        let je = JoinEngine{idx_this : &small, idx_other : &large};
+        let expected = vec![(1, 3), (2, 500), (3, 501), (4, 998)];
+        assert_eq!(pairs(&je, JoinStrategy::Merge), expected);
+        assert_eq!(pairs(&je, JoinStrategy::Hash), expected);
+        assert_eq!(pairs(&je, JoinStrategy::Auto), expected);
+        assert_eq!(je.get_inner_probe_joined_indicies().len(), expected.len());
+        let expected_left = vec![(0, None), (1, Some(3)), (2, Some(500)), (3, Some(501)), (4, Some(998)), (5, None)];
+        assert_eq!(left_pairs(&je, JoinStrategy::Merge), expected_left);
+        assert_eq!(left_pairs(&je, JoinStrategy::Hash), expected_left);
+        assert_eq!(left_pairs(&je, JoinStrategy::Auto), expected_left);
+
+        let je = JoinEngine{idx_this : &large, idx_other : &small};
+        let expected = vec![(3, 1), (500, 2), (501, 3), (998, 4)];
+        assert_eq!(pairs(&je, JoinStrategy::Merge), expected);
+        assert_eq!(pairs(&je, JoinStrategy::Hash), expected);
+        assert_eq!(pairs(&je, JoinStrategy::Auto), expected);
+        let left = left_pairs(&je, JoinStrategy::Auto);
+        assert_eq!(left, left_pairs(&je, JoinStrategy::Hash));
+        assert_eq!(left.iter().filter(|x| x.1.is_some()).count(), 4);
+
+        let empty = HashableIndex::new(vec![]);
+        let je = JoinEngine{idx_this : &small, idx_other : &empty};
+        assert!(pairs(&je, JoinStrategy::Auto).is_empty());
+        assert!(left_pairs(&je, JoinStrategy::Merge).iter().all(|x| x.1.is_none()));
+    }
+
+    #[test]
+    fn test_asof_join_exact_matches() {
+        let this = HashableIndex::new(vec![1, 2, 3, 4]);
+        let other = HashableIndex::new(vec![2, 3]);
+        let je = JoinEngine{idx_this : &this, idx_other : &other};
+        let asof_pairs = |other_idx_func: Box<dyn Fn(usize)->usize>, allow_exact_matches: bool| -> Vec<Option<usize>> {
+            je.get_asof_merge_joined_indicies_allow_exact(Some(crate::algo::int_utils::merge_asof_prior(5)), Some(other_idx_func), allow_exact_matches).iter().map(|x| x.other_idx).collect()
+        };
+        assert_eq!(asof_pairs(Box::new(prior_func), true), vec![None, Some(0), Some(1), Some(1)]);
+        assert_eq!(asof_pairs(Box::new(prior_func), false), vec![None, None, Some(0), Some(1)]);
+
+        let fwd_pairs = |allow_exact_matches: bool| -> Vec<Option<usize>> {
+            je.get_asof_merge_joined_indicies_allow_exact(Some(crate::algo::int_utils::merge_asof_fwd(5)), Some(Box::new(|idx| fwd_func(idx, 2))), allow_exact_matches).iter().map(|x| x.other_idx).collect()
+        };
+        assert_eq!(fwd_pairs(true), vec![Some(0), Some(0), Some(1), None]);
+        assert_eq!(fwd_pairs(false), vec![Some(0), Some(1), None, None]);
+    }
+}
@@ -11,18 +11,22 @@
 //! - `tsxlib::data_elements` =>  This contains the TimeSeriesDataPoint stuct, as the name would suggest it represents a point on a time series. You can use this to shuttle data around point by point as well as in any custom iterator implentations.
 //! - `tsxlib::index` => This module contains the struct that serves as the index for the timeseries container and associated methods.
 //! - `tsxlib::timeseries_iterators` => definitions/implementations for various timeseries iterators...i.e. skip/rolling...etc.
+//! - `tsxlib::numeric` => This contains the `NumericTimeSeries` extension trait with built-in methods (diff, pct_change, mean, quantile...etc.) for series of primitive numbers.
+//! - `tsxlib::error` => This contains the TsxError enum that is returned by the TimeSeries constructors and the IO methods.
 //! <br>
 //! ***IO Modules***
 //! - `tsxlib::io::*` => This module contains free funcs that can implement various IO methods. See the Readme for the implementation status matrix
 //! <br>
 //! ***Utility Modules***
 //! - `tsxlib::timeutils` => this contains utility functions that you can use on chrono datetimes to facilitate the bar-ing of data.
+//! - `tsxlib::bars` => this contains free funcs that turn a series of ticks into OHLCV bars (time, tick, volume and dollar bars).
 //! - `tsxlib::algo::chrono_utils` => this contains utility functions that you can use on chrono datetimes for the AsOf merge method on the TimeSeries struct.
 //! - `tsxlib::algo::int_utils` => this contains utility functions that you can use on ints for the AsOf merge method on the TimeSeries struct.
+//! - `tsxlib::algo::time_distance` => this contains the `TimeDistance` trait and the generic AsOf comparators built on it, implemented for all integer widths and the chrono timestamps.
 //! - `tsxlib::algo::macros` => this contains utility macros.
 //! <br>
 //! ***Internals***
-//! - `tsxlib::joins` => This module contains the implementation of the `JoinEngine` struct that implements the join algos that are used by TSXLIB. Both Hash Join and Merge Join are implemented but Merge Join is the one that is used due to its efficiency. In later versions of the crate we might expose hash join as an option
+//! - `tsxlib::joins` => This module contains the implementation of the `JoinEngine` struct that implements the join algos that are used by TSXLIB. Both Hash Join and Merge Join are implemented, Merge Join is used by default due to its efficiency but the inner and left joins can be given a `JoinStrategy` to pick hash join or let the engine choose based on the size of the indicies
 //! <br>
 //! **Note on compatibility**
 //! 
@@ -37,6 +41,7 @@
 //! Once the project stabilizes there will be effort put into maintaining compatibility with prior rust compiler versions
 
 
+pub mod error;
 pub mod joins;
 pub mod index;
 pub mod io;
@@ -45,3 +50,5 @@ pub mod data_elements;
 pub mod timeseries_iterators;
 pub mod timeutils;
 pub mod timeseries;
+pub mod numeric;
+pub mod bars;
@@ -0,0 +1,891 @@
+//! # Numeric Specializations
+//!
+//! Built-in methods for series of primitive numbers, i.e. `diff` or `mean`, so that you do not have to write a UDF for them every time.
+//!
+//! NaN handling follows pandas: the element-wise methods (`diff`, `pct_change`, `log_return`) propagate NaNs and return floats, while the aggregations skip them.
+//! An aggregation over a series without any non NaN values returns None.
+//!
+//! # Example
+//!
+//! ```
+//! use tsxlib::timeseries::TimeSeries;
+//! use tsxlib::numeric::NumericTimeSeries;
+//!
+//! let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4], vec![1.0, 3.0, f64::NAN, 8.0]).unwrap();
+//! assert_eq!(ts.diff(1).values()[0], 2.0);
+//! assert_eq!(ts.mean(), Some(4.0));
+//! assert_eq!(ts.argmax(), Some(4));
+//! ```
+//!
+//! The rolling aggregations (`rolling_sum`, `rolling_max`...etc.) are updated as points enter and leave the window rather than recomputed, and work with both count and time based windows
+//!
+//! ```
+//! use tsxlib::timeseries::TimeSeries;
+//! use tsxlib::numeric::NumericTimeSeries;
+//! use tsxlib::timeseries_iterators::{CountWindow, TimeWindow, WindowClosed};
+//!
+//! let ts = TimeSeries::from_vecs(vec![1, 2, 5, 6], vec![1.0, 3.0, 2.0, 8.0]).unwrap();
+//! let by_count: TimeSeries<i32,f64> = ts.rolling_max(CountWindow::new(2)).collect();
+//! assert_eq!(by_count.values(), vec![3.0, 3.0, 8.0]);
+//! let by_time: TimeSeries<i32,f64> = ts.rolling_max(TimeWindow::new(2i64, WindowClosed::Right, 1)).collect();
+//! assert_eq!(by_time.values(), vec![1.0, 3.0, 2.0, 8.0]);
+//! ```
+use std::cmp;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::{Add, Sub};
+
+use serde::Serialize;
+
+use crate::timeseries::{TimeSeries, InterpolationMode};
+use crate::index::HashableIndex;
+use crate::algo::time_distance::TimeStep;
+use crate::timeseries_iterators::{RollingAggregationIter, RollingAggregator, RollingWindow};
+use crate::error::TsxError;
+
+/// This trait defines the contract for the primitive number types that the `NumericTimeSeries` methods are implemented for
+pub trait Numeric: Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> {
+    fn zero() -> Self;
+    fn to_f64(self) -> f64;
+    /// only ever true for floats
+    fn is_nan(self) -> bool;
+}
+
+macro_rules! numeric_float_impl {
+    ($($t:ty)*) => ($(
+        impl Numeric for $t {
+            #[inline]
+            fn zero() -> $t {
+                0.0
+            }
+            #[inline]
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+            #[inline]
+            fn is_nan(self) -> bool {
+                <$t>::is_nan(self)
+            }
+        }
+    )*)
+}
+numeric_float_impl! { f32 f64 }
+
+macro_rules! numeric_int_impl {
+    ($($t:ty)*) => ($(
+        impl Numeric for $t {
+            #[inline]
+            fn zero() -> $t {
+                0
+            }
+            #[inline]
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+            #[inline]
+            fn is_nan(self) -> bool {
+                false
+            }
+        }
+    )*)
+}
+numeric_int_impl! { i8 i16 i32 i64 isize u8 u16 u32 u64 usize }
+
+/// Values that can be interpolated between, `weight` is the share of `other` in the result i.e. 0 returns self and 1 returns other. 
+/// Integers are rounded to the nearest value and an Option is None unless both sides are set. Implement it for your own value types to interpolate them with `TimeSeries::reindex_interpolated` or `interpolate`
+pub trait Interpolate: Sized {
+    fn interpolate(&self, other: &Self, weight: f64) -> Self;
+}
+
+macro_rules! interpolate_impl {
+    ($round:expr => $($t:ty)*) => ($(
+        impl Interpolate for $t {
+            #[inline]
+            fn interpolate(&self, other: &$t, weight: f64) -> $t {
+                let (a, b) = (*self as f64, *other as f64);
+                $round(a + (b - a) * weight) as $t
+            }
+        }
+    )*)
+}
+interpolate_impl! { |x: f64| x => f32 f64 }
+interpolate_impl! { f64::round => i8 i16 i32 i64 isize u8 u16 u32 u64 usize }
+
+impl<T: Interpolate> Interpolate for Option<T> {
+    fn interpolate(&self, other: &Option<T>, weight: f64) -> Option<T> {
+        match (self, other) {
+            (Some(a), Some(b)) => Some(a.interpolate(b, weight)),
+            _ => None
+        }
+    }
+}
+
+/// Summary statistics of a series, see `NumericTimeSeries::describe`
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct Description {
+    pub count: usize,
+    pub mean: Option<f64>,
+    pub std: Option<f64>,
+    pub min: Option<f64>,
+    pub q25: Option<f64>,
+    pub median: Option<f64>,
+    pub q75: Option<f64>,
+    pub max: Option<f64>
+}
+
+/// Extension methods for TimeSeries of primitive numbers
+pub trait NumericTimeSeries<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Numeric> {
+    /// difference with the value `periods` points earlier, the first `periods` points are dropped. Like pandas the differences are floats, so a decrease of an unsigned series is negative
+    fn diff(&self, periods: usize) -> TimeSeries<TDate,f64>;
+    /// relative change with the value `periods` points earlier, the first `periods` points are dropped
+    fn pct_change(&self, periods: usize) -> TimeSeries<TDate,f64>;
+    /// natural log of the ratio with the prior value, the first point is dropped
+    fn log_return(&self) -> TimeSeries<TDate,f64>;
+    /// number of non NaN values
+    fn count(&self) -> usize;
+    /// sum of the non NaN values as f64 so integer series cannot overflow, zero for an empty series
+    fn sum(&self) -> f64;
+    fn mean(&self) -> Option<f64>;
+    /// sample variance (i.e. divided by n - 1), None with less than 2 values
+    fn var(&self) -> Option<f64>;
+    /// sample standard deviation, None with less than 2 values
+    fn std(&self) -> Option<f64>;
+    fn min(&self) -> Option<T>;
+    fn max(&self) -> Option<T>;
+    /// timestamp of the first occurrence of the minimum
+    fn argmin(&self) -> Option<TDate>;
+    /// timestamp of the first occurrence of the maximum
+    fn argmax(&self) -> Option<TDate>;
+    /// quantile `q` in [0, 1] of the values, interpolating linearly between the closest points (like pandas). Returns an error if `q` is out of range
+    fn quantile(&self, q: f64) -> Result<Option<f64>, TsxError>;
+    /// count, mean, std, min, quartiles and max in one go
+    fn describe(&self) -> Description;
+    /// rolling number of non NaN values, the window is either a `CountWindow` or a `TimeWindow`
+    fn rolling_count<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingCount>;
+    /// rolling sum of the non NaN values
+    fn rolling_sum<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingSum>;
+    /// rolling mean of the non NaN values
+    fn rolling_mean<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingMean>;
+    /// rolling sample variance of the non NaN values
+    fn rolling_var<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingVar>;
+    /// rolling sample standard deviation of the non NaN values
+    fn rolling_std<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingVar>;
+    /// rolling minimum of the non NaN values
+    fn rolling_min<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingExtreme>;
+    /// rolling maximum of the non NaN values
+    fn rolling_max<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingExtreme>;
+    /// rolling median of the non NaN values
+    fn rolling_median<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingQuantile>;
+    /// rolling quantile `q` in [0, 1] of the non NaN values, interpolated the same way as `quantile`. Returns an error if `q` is out of range
+    fn rolling_quantile<W: RollingWindow<TDate>>(&self, window: W, q: f64) -> Result<RollingAggregationIter<'_,TDate,T,W,RollingQuantile>, TsxError>;
+    /// replace NaNs with the last non NaN value, at most `limit` in a row
+    fn ffill(&self, limit: Option<usize>) -> TimeSeries<TDate,T>;
+    /// replace NaNs with the next non NaN value, at most `limit` in a row
+    fn bfill(&self, limit: Option<usize>) -> TimeSeries<TDate,T>;
+    /// replace all NaNs with `value`
+    fn fillna(&self, value: T) -> TimeSeries<TDate,T>;
+    /// drop the NaN points
+    fn dropna(&self) -> TimeSeries<TDate,T>;
+    /// replace NaNs from the values around them, see `TimeSeries::interpolate`
+    fn interpolate(&self, mode: InterpolationMode) -> TimeSeries<TDate,T> where TDate: TimeStep, T: Interpolate;
+}
+
+/// apply `func(prior, curr)` to every value and the one `periods` points before it
+fn lagged_apply<TDate, T, TRes, F>(ts: &TimeSeries<TDate,T>, periods: usize, func: F) -> TimeSeries<TDate,TRes>
+where
+    TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord,
+    T: Numeric,
+    TRes: Clone,
+    F: Fn(T, T) -> TRes
+{
+    if periods >= ts.len() {
+        return TimeSeries::empty();
+    }
+    let values = ts.values().iter().zip(ts.values()[periods..].iter()).map(|(prior, curr)| func(*prior, *curr)).collect();
+    let index = ts.index().values()[periods..].to_vec();
+    TimeSeries::from_vecs_unchecked(HashableIndex::new(index), values)
+}
+
+fn nan_as_none<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Numeric>(ts: &TimeSeries<TDate,T>) -> TimeSeries<TDate,Option<T>> {
+    ts.map(|v| match v.is_nan() {
+        true => None,
+        false => Some(*v)
+    })
+}
+
+/// the values of `filled`, keeping the NaNs of `ts` where nothing was filled in
+fn with_nans<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Numeric>(filled: TimeSeries<TDate,Option<T>>, ts: &TimeSeries<TDate,T>) -> TimeSeries<TDate,T> {
+    let (index, filled) = filled.into_parts();
+    let values = filled.iter().zip(ts.values().iter()).map(|(f, v)| f.unwrap_or(*v)).collect();
+    TimeSeries::from_vecs_unchecked(index, values)
+}
+
+fn sorted_non_nan<T: Numeric>(values: &[T]) -> Vec<f64> {
+    let mut sorted: Vec<f64> = values.iter().filter(|v| !v.is_nan()).map(|v| v.to_f64()).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    sorted
+}
+
+fn check_quantile(q: f64) -> Result<f64, TsxError> {
+    match (0.0..=1.0).contains(&q) {
+        true => Ok(q),
+        false => Err(TsxError::InvalidParameter{ name: "quantile", value: q.to_string(), expected: "in [0, 1]" })
+    }
+}
+
+/// linear interpolation between the closest order statistics of already sorted values, `q` has to be in [0, 1]
+pub(crate) fn quantile_of_sorted(sorted: &[f64], q: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = q * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+fn arg_extreme<T: Numeric>(values: &[T], replace: cmp::Ordering) -> Option<usize> {
+    values.iter().enumerate()
+        .filter(|(_, v)| !v.is_nan())
+        .fold(None, |best: Option<(usize, T)>, (pos, v)| match best {
+            Some((_, b)) if v.partial_cmp(&b) != Some(replace) => best,
+            _ => Some((pos, *v))
+        })
+        .map(|(pos, _)| pos)
+}
+
+impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Numeric> NumericTimeSeries<TDate,T> for TimeSeries<TDate,T> {
+    fn diff(&self, periods: usize) -> TimeSeries<TDate,f64> {
+        lagged_apply(self, periods, |prior, curr| curr.to_f64() - prior.to_f64())
+    }
+
+    fn pct_change(&self, periods: usize) -> TimeSeries<TDate,f64> {
+        lagged_apply(self, periods, |prior, curr| curr.to_f64() / prior.to_f64() - 1.0)
+    }
+
+    fn log_return(&self) -> TimeSeries<TDate,f64> {
+        lagged_apply(self, 1, |prior, curr| (curr.to_f64() / prior.to_f64()).ln())
+    }
+
+    fn count(&self) -> usize {
+        self.values().iter().filter(|v| !v.is_nan()).count()
+    }
+
+    fn sum(&self) -> f64 {
+        self.values().iter().filter(|v| !v.is_nan()).map(|v| v.to_f64()).sum()
+    }
+
+    fn mean(&self) -> Option<f64> {
+        let (count, total) = self.values().iter().filter(|v| !v.is_nan()).fold((0usize, 0.0), |(count, total), v| (count + 1, total + v.to_f64()));
+        match count {
+            0 => None,
+            _ => Some(total / count as f64)
+        }
+    }
+
+    fn var(&self) -> Option<f64> {
+        let count = self.count();
+        if count < 2 {
+            return None;
+        }
+        let mean = self.mean()?;
+        let sum_sq = self.values().iter().filter(|v| !v.is_nan()).map(|v| (v.to_f64() - mean) * (v.to_f64() - mean)).sum::<f64>();
+        Some(sum_sq / (count - 1) as f64)
+    }
+
+    fn std(&self) -> Option<f64> {
+        self.var().map(f64::sqrt)
+    }
+
+    fn min(&self) -> Option<T> {
+        arg_extreme(self.values(), cmp::Ordering::Less).map(|pos| self.values()[pos])
+    }
+
+    fn max(&self) -> Option<T> {
+        arg_extreme(self.values(), cmp::Ordering::Greater).map(|pos| self.values()[pos])
+    }
+
+    fn argmin(&self) -> Option<TDate> {
+        arg_extreme(self.values(), cmp::Ordering::Less).map(|pos| self.index()[pos].clone())
+    }
+
+    fn argmax(&self) -> Option<TDate> {
+        arg_extreme(self.values(), cmp::Ordering::Greater).map(|pos| self.index()[pos].clone())
+    }
+
+    fn quantile(&self, q: f64) -> Result<Option<f64>, TsxError> {
+        Ok(quantile_of_sorted(&sorted_non_nan(self.values()), check_quantile(q)?))
+    }
+
+    fn describe(&self) -> Description {
+        let sorted = sorted_non_nan(self.values());
+        Description {
+            count: sorted.len(),
+            mean: self.mean(),
+            std: self.std(),
+            min: sorted.first().copied(),
+            q25: quantile_of_sorted(&sorted, 0.25),
+            median: quantile_of_sorted(&sorted, 0.5),
+            q75: quantile_of_sorted(&sorted, 0.75),
+            max: sorted.last().copied()
+        }
+    }
+
+    fn rolling_count<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingCount> {
+        RollingAggregationIter::new(self, window, RollingCount::default())
+    }
+
+    fn rolling_sum<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingSum> {
+        RollingAggregationIter::new(self, window, RollingSum::default())
+    }
+
+    fn rolling_mean<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingMean> {
+        RollingAggregationIter::new(self, window, RollingMean::default())
+    }
+
+    fn rolling_var<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingVar> {
+        RollingAggregationIter::new(self, window, RollingVar::variance())
+    }
+
+    fn rolling_std<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingVar> {
+        RollingAggregationIter::new(self, window, RollingVar::std())
+    }
+
+    fn rolling_min<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingExtreme> {
+        RollingAggregationIter::new(self, window, RollingExtreme::min())
+    }
+
+    fn rolling_max<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingExtreme> {
+        RollingAggregationIter::new(self, window, RollingExtreme::max())
+    }
+
+    fn rolling_median<W: RollingWindow<TDate>>(&self, window: W) -> RollingAggregationIter<'_,TDate,T,W,RollingQuantile> {
+        RollingAggregationIter::new(self, window, RollingQuantile::with_q(0.5))
+    }
+
+    fn rolling_quantile<W: RollingWindow<TDate>>(&self, window: W, q: f64) -> Result<RollingAggregationIter<'_,TDate,T,W,RollingQuantile>, TsxError> {
+        Ok(RollingAggregationIter::new(self, window, RollingQuantile::new(q)?))
+    }
+
+    fn ffill(&self, limit: Option<usize>) -> TimeSeries<TDate,T> {
+        with_nans(nan_as_none(self).ffill(limit), self)
+    }
+
+    fn bfill(&self, limit: Option<usize>) -> TimeSeries<TDate,T> {
+        with_nans(nan_as_none(self).bfill(limit), self)
+    }
+
+    fn fillna(&self, value: T) -> TimeSeries<TDate,T> {
+        nan_as_none(self).fillna(value)
+    }
+
+    fn dropna(&self) -> TimeSeries<TDate,T> {
+        nan_as_none(self).dropna()
+    }
+
+    fn interpolate(&self, mode: InterpolationMode) -> TimeSeries<TDate,T> where TDate: TimeStep, T: Interpolate {
+        with_nans(nan_as_none(self).interpolate(mode), self)
+    }
+}
+
+/// Rolling number of non NaN values
+#[derive(Clone,Debug,Default)]
+pub struct RollingCount {
+    count: usize
+}
+
+impl<T: Numeric> RollingAggregator<T> for RollingCount {
+    fn push(&mut self, _pos: usize, value: &T) {
+        if !value.is_nan() {
+            self.count += 1;
+        }
+    }
+
+    fn pop(&mut self, _pos: usize, value: &T) {
+        if !value.is_nan() {
+            self.count -= 1;
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        Some(self.count as f64)
+    }
+}
+
+/// Rolling sum of the non NaN values, None if the window has none
+#[derive(Clone,Debug,Default)]
+pub struct RollingSum {
+    count: usize,
+    sum: f64
+}
+
+impl<T: Numeric> RollingAggregator<T> for RollingSum {
+    fn push(&mut self, _pos: usize, value: &T) {
+        if !value.is_nan() {
+            self.count += 1;
+            self.sum += value.to_f64();
+        }
+    }
+
+    fn pop(&mut self, _pos: usize, value: &T) {
+        if !value.is_nan() {
+            self.count -= 1;
+            // start from scratch once the window is empty so that rounding errors do not accumulate
+            self.sum = match self.count {
+                0 => 0.0,
+                _ => self.sum - value.to_f64()
+            };
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            _ => Some(self.sum)
+        }
+    }
+}
+
+/// Rolling mean of the non NaN values, None if the window has none
+#[derive(Clone,Debug,Default)]
+pub struct RollingMean {
+    sum: RollingSum
+}
+
+impl<T: Numeric> RollingAggregator<T> for RollingMean {
+    fn push(&mut self, pos: usize, value: &T) {
+        self.sum.push(pos, value);
+    }
+
+    fn pop(&mut self, pos: usize, value: &T) {
+        self.sum.pop(pos, value);
+    }
+
+    fn value(&self) -> Option<f64> {
+        <RollingSum as RollingAggregator<T>>::value(&self.sum).map(|sum| sum / self.sum.count as f64)
+    }
+}
+
+/// Rolling sample variance (or standard deviation) of the non NaN values via Welford's algorithm, None if the window has less than 2 values
+#[derive(Clone,Debug)]
+pub struct RollingVar {
+    count: usize,
+    mean: f64,
+    m2: f64,
+    sqrt: bool
+}
+
+impl RollingVar {
+    pub fn variance() -> RollingVar {
+        RollingVar { count: 0, mean: 0.0, m2: 0.0, sqrt: false }
+    }
+
+    pub fn std() -> RollingVar {
+        RollingVar { count: 0, mean: 0.0, m2: 0.0, sqrt: true }
+    }
+}
+
+impl<T: Numeric> RollingAggregator<T> for RollingVar {
+    fn push(&mut self, _pos: usize, value: &T) {
+        if !value.is_nan() {
+            let x = value.to_f64();
+            self.count += 1;
+            let delta = x - self.mean;
+            self.mean += delta / self.count as f64;
+            self.m2 += delta * (x - self.mean);
+        }
+    }
+
+    fn pop(&mut self, _pos: usize, value: &T) {
+        if !value.is_nan() {
+            let x = value.to_f64();
+            self.count -= 1;
+            if self.count == 0 {
+                self.mean = 0.0;
+                self.m2 = 0.0;
+                return;
+            }
+            let delta = x - self.mean;
+            self.mean -= delta / self.count as f64;
+            self.m2 = (self.m2 - delta * (x - self.mean)).max(0.0);
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        if self.count < 2 {
+            return None;
+        }
+        let var = self.m2 / (self.count - 1) as f64;
+        match self.sqrt {
+            true => Some(var.sqrt()),
+            false => Some(var)
+        }
+    }
+}
+
+/// Rolling minimum (or maximum) of the non NaN values via a monotonic deque, None if the window has none
+#[derive(Clone,Debug)]
+pub struct RollingExtreme {
+    // positions and values of the candidates, the front is the current extreme
+    candidates: VecDeque<(usize, f64)>,
+    keep: cmp::Ordering
+}
+
+impl RollingExtreme {
+    pub fn min() -> RollingExtreme {
+        RollingExtreme { candidates: VecDeque::new(), keep: cmp::Ordering::Less }
+    }
+
+    pub fn max() -> RollingExtreme {
+        RollingExtreme { candidates: VecDeque::new(), keep: cmp::Ordering::Greater }
+    }
+}
+
+impl<T: Numeric> RollingAggregator<T> for RollingExtreme {
+    fn push(&mut self, pos: usize, value: &T) {
+        if value.is_nan() {
+            return;
+        }
+        let x = value.to_f64();
+        // a candidate that is not better than the new value can never be the extreme again
+        while let Some(&(_, back)) = self.candidates.back() {
+            if back.partial_cmp(&x) == Some(self.keep) {
+                break;
+            }
+            self.candidates.pop_back();
+        }
+        self.candidates.push_back((pos, x));
+    }
+
+    fn pop(&mut self, pos: usize, _value: &T) {
+        if let Some(&(front, _)) = self.candidates.front() {
+            if front == pos {
+                self.candidates.pop_front();
+            }
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.candidates.front().map(|&(_, x)| x)
+    }
+}
+
+// an f64 that is never NaN, so that it can be put in a heap
+#[derive(Clone,Copy,Debug,PartialEq)]
+struct HeapF64(f64);
+
+impl Eq for HeapF64 {}
+
+impl PartialOrd for HeapF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapF64 {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
+
+/// Rolling quantile of the non NaN values via two heaps with lazy deletion, None if the window has none
+///
+/// The lower heap holds the values up to (and including) the lower of the two order statistics that the quantile is interpolated from, the upper heap the rest.
+#[derive(Clone,Debug)]
+pub struct RollingQuantile {
+    q: f64,
+    lower: BinaryHeap<(HeapF64, usize)>,
+    upper: BinaryHeap<cmp::Reverse<(HeapF64, usize)>>,
+    // positions that left the window but are still in one of the heaps
+    deleted: HashSet<usize>,
+    lower_len: usize,
+    upper_len: usize
+}
+
+impl RollingQuantile {
+    /// rolling quantile `q`, which has to be in [0, 1]
+    pub fn new(q: f64) -> Result<RollingQuantile, TsxError> {
+        Ok(RollingQuantile::with_q(check_quantile(q)?))
+    }
+
+    fn with_q(q: f64) -> RollingQuantile {
+        RollingQuantile { q, lower: BinaryHeap::new(), upper: BinaryHeap::new(), deleted: HashSet::new(), lower_len: 0, upper_len: 0 }
+    }
+
+    fn rank(&self) -> f64 {
+        self.q * (self.lower_len + self.upper_len - 1) as f64
+    }
+
+    fn prune(&mut self) {
+        while let Some(&(_, pos)) = self.lower.peek() {
+            if !self.deleted.remove(&pos) {
+                break;
+            }
+            self.lower.pop();
+        }
+        while let Some(&cmp::Reverse((_, pos))) = self.upper.peek() {
+            if !self.deleted.remove(&pos) {
+                break;
+            }
+            self.upper.pop();
+        }
+    }
+
+    fn rebalance(&mut self) {
+        self.prune();
+        let target = match self.lower_len + self.upper_len {
+            0 => 0,
+            _ => self.rank().floor() as usize + 1
+        };
+        while self.lower_len > target {
+            let top = self.lower.pop().unwrap();
+            self.upper.push(cmp::Reverse(top));
+            self.lower_len -= 1;
+            self.upper_len += 1;
+            self.prune();
+        }
+        while self.lower_len < target {
+            let cmp::Reverse(top) = self.upper.pop().unwrap();
+            self.lower.push(top);
+            self.lower_len += 1;
+            self.upper_len -= 1;
+            self.prune();
+        }
+    }
+
+    // rebuild a heap once it is mostly made of deleted values, so that memory stays bounded by the window
+    fn compact(&mut self) {
+        let deleted = &mut self.deleted;
+        if self.lower.len() > 2 * self.lower_len + 32 {
+            let mut kept = std::mem::take(&mut self.lower).into_vec();
+            kept.retain(|(_, pos)| !deleted.remove(pos));
+            self.lower = BinaryHeap::from(kept);
+        }
+        if self.upper.len() > 2 * self.upper_len + 32 {
+            let mut kept = std::mem::take(&mut self.upper).into_vec();
+            kept.retain(|cmp::Reverse((_, pos))| !deleted.remove(pos));
+            self.upper = BinaryHeap::from(kept);
+        }
+    }
+}
+
+impl<T: Numeric> RollingAggregator<T> for RollingQuantile {
+    fn push(&mut self, pos: usize, value: &T) {
+        if value.is_nan() {
+            return;
+        }
+        let key = (HeapF64(value.to_f64()), pos);
+        match self.lower.peek() {
+            Some(top) if key > *top => {
+                self.upper.push(cmp::Reverse(key));
+                self.upper_len += 1;
+            },
+            _ => {
+                self.lower.push(key);
+                self.lower_len += 1;
+            }
+        }
+        self.rebalance();
+    }
+
+    fn pop(&mut self, pos: usize, value: &T) {
+        if value.is_nan() {
+            return;
+        }
+        let key = (HeapF64(value.to_f64()), pos);
+        // the tops are never deleted values, so the key is in the lower heap iff it is not above its top
+        match self.lower.peek() {
+            Some(top) if key <= *top => self.lower_len -= 1,
+            _ => self.upper_len -= 1
+        }
+        self.deleted.insert(pos);
+        self.rebalance();
+        self.compact();
+    }
+
+    fn value(&self) -> Option<f64> {
+        let &(HeapF64(low), _) = self.lower.peek()?;
+        let rank = self.rank();
+        let fraction = rank - rank.floor();
+        match self.upper.peek() {
+            Some(&cmp::Reverse((HeapF64(high), _))) if fraction > 0.0 => Some(low + (high - low) * fraction),
+            _ => Some(low)
+        }
+    }
+}
+
+/// -----------------------------------------------------------------------------------------------------------------------------------------
+/// Unit Test Area
+/// -----------------------------------------------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeseries_iterators::{CountWindow, TimeWindow, WindowClosed};
+
+    #[test]
+    fn test_elementwise() {
+        let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4], vec![1.0, 2.0, f64::NAN, 8.0]).unwrap();
+        let diff = ts.diff(1);
+        assert_eq!(diff.index().values(), vec![2, 3, 4]);
+        assert_eq!(diff.values()[0], 1.0);
+        assert!(diff.values()[1].is_nan() && diff.values()[2].is_nan());
+        assert_eq!(ts.diff(2).values()[1], 6.0);
+        assert_eq!(ts.pct_change(1).values()[0], 1.0);
+        assert_eq!(ts.log_return().values()[0], 2f64.ln());
+        assert!(ts.diff(4).is_empty());
+        assert!(ts.diff(10).is_empty());
+
+        let ints = TimeSeries::from_vecs(vec![1, 2, 3], vec![5i64, 3, 10]).unwrap();
+        assert_eq!(ints.diff(1).values(), vec![-2.0, 7.0]);
+
+        let volumes = TimeSeries::from_vecs(vec![1, 2, 3, 4], vec![10u32, 4, 4, u32::MAX]).unwrap();
+        assert_eq!(volumes.diff(1).values(), vec![-6.0, 0.0, (u32::MAX - 4) as f64]);
+        let small = TimeSeries::from_vecs(vec![1, 2], vec![200u8, 0]).unwrap();
+        assert_eq!(small.diff(1).values(), vec![-200.0]);
+    }
+
+    #[test]
+    fn test_aggregations() {
+        let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4, 5], vec![4.0, f64::NAN, 1.0, 7.0, 1.0]).unwrap();
+        assert_eq!(ts.count(), 4);
+        assert_eq!(ts.sum(), 13.0);
+        assert_eq!(ts.mean(), Some(3.25));
+        assert_eq!(ts.var(), Some(8.25));
+        assert_eq!(ts.std(), Some(8.25f64.sqrt()));
+        assert_eq!(ts.min(), Some(1.0));
+        assert_eq!(ts.max(), Some(7.0));
+        assert_eq!(ts.argmin(), Some(3));
+        assert_eq!(ts.argmax(), Some(4));
+        assert_eq!(ts.quantile(0.5).unwrap(), Some(2.5));
+        assert_eq!(ts.quantile(0.0).unwrap(), Some(1.0));
+        assert_eq!(ts.quantile(1.0).unwrap(), Some(7.0));
+        assert_eq!(ts.quantile(0.75).unwrap(), Some(4.75));
+        assert!(matches!(ts.quantile(1.5), Err(TsxError::InvalidParameter{ name: "quantile", .. })));
+        assert!(ts.quantile(-0.1).is_err());
+        assert!(ts.quantile(f64::NAN).is_err());
+        assert!(ts.rolling_quantile(CountWindow::new(2), 2.0).is_err());
+        assert!(RollingQuantile::new(-1.0).is_err());
+
+        let description = ts.describe();
+        assert_eq!(description.count, 4);
+        assert_eq!(description.median, Some(2.5));
+        assert_eq!(description.min, Some(1.0));
+        assert_eq!(description.max, Some(7.0));
+
+        let ints = TimeSeries::from_vecs(vec![1, 2, 3], vec![3u32, 9, 6]).unwrap();
+        assert_eq!(ints.sum(), 18.0);
+        assert_eq!(ints.mean(), Some(6.0));
+        assert_eq!(ints.argmax(), Some(2));
+
+        // integer sums would overflow the value type
+        let bytes = TimeSeries::from_vecs(vec![1, 2], vec![200u8, 100]).unwrap();
+        assert_eq!(bytes.sum(), 300.0);
+        assert_eq!(bytes.mean(), Some(150.0));
+        assert_eq!(bytes.var(), Some(5000.0));
+        assert_eq!(bytes.describe().mean, Some(150.0));
+        let large = TimeSeries::from_vecs(vec![1, 2], vec![i64::MAX, i64::MAX]).unwrap();
+        assert_eq!(large.sum(), 2.0 * i64::MAX as f64);
+    }
+
+    #[test]
+    fn test_aggregations_without_values() {
+        let nans = TimeSeries::from_vecs(vec![1, 2], vec![f64::NAN, f64::NAN]).unwrap();
+        assert_eq!(nans.count(), 0);
+        assert_eq!(nans.sum(), 0.0);
+        assert_eq!(nans.mean(), None);
+        assert_eq!(nans.min(), None);
+        assert_eq!(nans.argmax(), None);
+        assert_eq!(nans.quantile(0.5).unwrap(), None);
+
+        let single = TimeSeries::from_vecs(vec![1], vec![2.0f32]).unwrap();
+        assert_eq!(single.var(), None);
+        assert_eq!(single.describe(), Description { count: 1, mean: Some(2.0), std: None, min: Some(2.0), q25: Some(2.0), median: Some(2.0), q75: Some(2.0), max: Some(2.0) });
+    }
+
+    fn pseudo_random_series() -> TimeSeries<i64,f64> {
+        // irregular stamps with repeated values and some NaNs
+        let index: Vec<i64> = (0..300).map(|i| i * 5 + (i * 7) % 5).collect();
+        let values = (0..300).map(|i: i64| match i % 17 {
+            0 => f64::NAN,
+            _ => ((i * 7919) % 101) as f64 / 4.0
+        }).collect();
+        TimeSeries::from_vecs(index, values).unwrap()
+    }
+
+    fn assert_rolling_matches<W: RollingWindow<i64> + Clone, F: Fn(&[f64]) -> Option<f64>>(ts: &TimeSeries<i64,f64>, window: W, rolled: TimeSeries<i64,f64>, naive: F) {
+        let mut bounds_window = window;
+        let mut expected = Vec::new();
+        for pos in 0..ts.len() {
+            let bounds = bounds_window.advance_to(ts.index().values(), pos);
+            if !bounds_window.is_complete(&bounds) {
+                continue;
+            }
+            let finite: Vec<f64> = ts.values()[bounds].iter().copied().filter(|v| !v.is_nan()).collect();
+            if let Some(v) = naive(&finite) {
+                expected.push((ts.index()[pos], v));
+            }
+        }
+        assert_eq!(rolled.len(), expected.len());
+        for (dp, (stamp, v)) in rolled.into_iter().zip(expected) {
+            assert_eq!(dp.timestamp, stamp);
+            assert!((dp.value - v).abs() < 1e-9, "{} != {} at {}", dp.value, v, stamp);
+        }
+    }
+
+    fn naive_quantile(values: &[f64], q: f64) -> Option<f64> {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        quantile_of_sorted(&sorted, q)
+    }
+
+    fn check_all_rolling<W: RollingWindow<i64> + Clone>(ts: &TimeSeries<i64,f64>, window: W) {
+        let as_series = |dps: Vec<crate::data_elements::TimeSeriesDataPoint<i64,f64>>| TimeSeries::from_tsdatapoints(dps).unwrap();
+        let sorted_series = |values: &[f64]| TimeSeries::from_vecs((0..values.len() as i64).collect(), values.to_vec()).unwrap();
+        assert_rolling_matches(ts, window.clone(), as_series(ts.rolling_count(window.clone()).collect()), |v| Some(v.len() as f64));
+        assert_rolling_matches(ts, window.clone(), as_series(ts.rolling_sum(window.clone()).collect()), |v| if v.is_empty() { None } else { Some(v.iter().sum()) });
+        assert_rolling_matches(ts, window.clone(), as_series(ts.rolling_mean(window.clone()).collect()), |v| sorted_series(v).mean());
+        assert_rolling_matches(ts, window.clone(), as_series(ts.rolling_var(window.clone()).collect()), |v| sorted_series(v).var());
+        assert_rolling_matches(ts, window.clone(), as_series(ts.rolling_std(window.clone()).collect()), |v| sorted_series(v).std());
+        assert_rolling_matches(ts, window.clone(), as_series(ts.rolling_min(window.clone()).collect()), |v| sorted_series(v).min());
+        assert_rolling_matches(ts, window.clone(), as_series(ts.rolling_max(window.clone()).collect()), |v| sorted_series(v).max());
+        assert_rolling_matches(ts, window.clone(), as_series(ts.rolling_median(window.clone()).collect()), |v| naive_quantile(v, 0.5));
+        for q in [0.0, 0.1, 0.75, 1.0].iter() {
+            assert_rolling_matches(ts, window.clone(), as_series(ts.rolling_quantile(window.clone(), *q).unwrap().collect()), |v| naive_quantile(v, *q));
+        }
+    }
+
+    #[test]
+    fn test_missing_values() {
+        let ts = TimeSeries::from_vecs(vec![0, 1, 2, 4, 5], vec![f64::NAN, 1.0, f64::NAN, f64::NAN, 4.0]).unwrap();
+        let ffilled = ts.ffill(Some(1));
+        assert!(ffilled.values()[0].is_nan() && ffilled.values()[3].is_nan());
+        assert_eq!(ffilled.values()[1..3], [1.0, 1.0]);
+        assert_eq!(ts.bfill(None).values(), vec![1.0, 1.0, 4.0, 4.0, 4.0]);
+        assert_eq!(ts.fillna(0.0).values(), vec![0.0, 1.0, 0.0, 0.0, 4.0]);
+        assert_eq!(ts.dropna().index().values(), vec![1, 5]);
+        let interpolated = ts.interpolate(InterpolationMode::Time);
+        assert!(interpolated.values()[0].is_nan());
+        assert_eq!(interpolated.values()[1..], [1.0, 1.75, 3.25, 4.0]);
+
+        let ints = TimeSeries::from_vecs(vec![1, 2], vec![3, 4]).unwrap();
+        assert_eq!(ints.ffill(None), ints);
+        assert_eq!(ints.dropna(), ints);
+    }
+
+    #[test]
+    fn test_rolling_count_window() {
+        let ts = pseudo_random_series();
+        for size in [1, 2, 5, 40].iter() {
+            check_all_rolling(&ts, CountWindow::new(*size));
+        }
+        assert_eq!(ts.rolling_sum(CountWindow::new(301)).count(), 0);
+    }
+
+    #[test]
+    fn test_rolling_time_window() {
+        let ts = pseudo_random_series();
+        for closed in [WindowClosed::Right, WindowClosed::Left, WindowClosed::Both, WindowClosed::Neither].iter() {
+            check_all_rolling(&ts, TimeWindow::new(10i128, *closed, 1));
+            check_all_rolling(&ts, TimeWindow::new(100i128, *closed, 20));
+        }
+    }
+}
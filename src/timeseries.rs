@@ -6,19 +6,94 @@ use itertools::Itertools;
 use serde::{Serialize};
 
 
-use crate::timeseries_iterators::{OrderedTimeSeriesIter, ShiftedTimeSeriesIter, RollingTimeSeriesIter,RollingTimeSeriesIterWithUpdate,FromUncheckedIterator,TimeSeriesRefIter,OrderedTimeSeriesRefIter, TimeSeriesIter, SkipApplyTimeSeriesIter};
+use crate::timeseries_iterators::{OrderedTimeSeriesIter, ShiftedTimeSeriesIter, RollingTimeSeriesIter,RollingTimeSeriesIterWithUpdate,AlignedRollingTimeSeriesIter,RollingOptions,TimeRollingTimeSeriesIter,TimeRollingTimeSeriesIterWithUpdate,WindowClosed,ExpandingTimeSeriesIter,EwmTimeSeriesIter,EwmDecay,EwmState,FromUncheckedIterator,TimeSeriesRefIter,OrderedTimeSeriesRefIter, TimeSeriesIter, SkipApplyTimeSeriesIter};
 use crate::data_elements::TimeSeriesDataPoint;
 use crate::index::HashableIndex;
-use crate::joins::{JoinEngine};
+use crate::joins::{JoinEngine,JoinStrategy,IndexJoinPotentiallyUnmatchedPair,IndexJoinBracket};
+use crate::error::TsxError;
+use crate::algo::time_distance::{AsofCompareFunc, TimeDistance, TimeStep};
+use crate::timeutils;
+use crate::numeric::Interpolate;
+
+/// MergeAsofMode describes the roll behavior of the asof merge, `Nearest` looks both ways and is meant to be used with a nearest comperator i.e. `chrono_utils::merge_asof_nearest`
+pub enum MergeAsofMode{ RollPrior, RollFollowing, Nearest, NoRoll}
+
+/// BinEdge picks an edge of a resampling bin, it is used both for the edge a bin is labeled with and for the edge that is closed (i.e. belongs to the bin)
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum BinEdge{ Left, Right}
+
+/// FillEmpty describes what resampling reports for a bin without points, `Null` reports None while `ForwardFill` repeats the prior bin
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum FillEmpty{ Skip, Null, ForwardFill}
+
+/// ResampleOrigin is the key the resampling grid is anchored at, `Start` being the first key of the series
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum ResampleOrigin<TDate>{ Start, At(TDate)}
+
+/// Options of `TimeSeries::resample`, the grid is anchored at `origin` moved by `offset`. The default closes and labels bins on the left, anchors at the first key and skips empty bins
+#[derive(Clone)]
+pub struct ResampleOptions<TDate: TimeStep> {
+    pub label: BinEdge,
+    pub closed: BinEdge,
+    pub origin: ResampleOrigin<TDate>,
+    pub offset: TDate::Delta,
+    pub fill_empty: FillEmpty
+}
+
+impl<TDate: TimeStep> Default for ResampleOptions<TDate> {
+    fn default() -> Self {
+        ResampleOptions { label: BinEdge::Left, closed: BinEdge::Left, origin: ResampleOrigin::Start, offset: TDate::zero_delta(), fill_empty: FillEmpty::Skip }
+    }
+}
 
-/// MergeAsofMode describes the roll behavior of the asof merge
-pub enum MergeAsofMode{ RollPrior, RollFollowing, NoRoll}
+/// FillPolicy describes how `TimeSeries::reindex` fills keys of the target index that are not in the series. 
+/// `limit` caps the number of consecutive keys filled from the same point and `tolerance` the distance to the nearest point (ties go to the prior point).
+/// None of them touch the values, so any series can be reindexed, use `TimeSeries::reindex_interpolated` to interpolate between the points instead
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum FillPolicy<TDelta>{ None, ForwardFill{ limit: Option<usize> }, BackwardFill{ limit: Option<usize> }, Nearest{ tolerance: Option<TDelta> }}
+
+/// InterpolationMode describes how `interpolate` fills missing values. `Linear` treats the points as equally spaced while `Time` weights by the distance between the keys, 
+/// `Nearest` takes the value with the closest key (ties go to the prior value) and `Previous` the last value
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum InterpolationMode{ Linear, Time, Nearest, Previous}
+
+/// DuplicatePolicy describes what happens to points that share a timestamp, `KeepFirst` keeps the value that came first (i.e. the existing value on insert) while `KeepLast` keeps the one that came last. 
+/// `Aggregate` combines the values in the order they came in, the closure can capture its environment (i.e. a weight or a rate), see `DuplicatePolicy::aggregate`
+pub enum DuplicatePolicy<T>{ Error, KeepFirst, KeepLast, Aggregate(DuplicateAggregator<T>)}
+
+/// the closure that an `Aggregate` duplicate policy combines the values with
+pub type DuplicateAggregator<T> = Box<dyn Fn(&[T]) -> T>;
+
+impl<T> DuplicatePolicy<T> {
+    /// combine the values that share a timestamp with `agg_func`
+    pub fn aggregate<F: Fn(&[T]) -> T + 'static>(agg_func: F) -> DuplicatePolicy<T> {
+        DuplicatePolicy::Aggregate(Box::new(agg_func))
+    }
+}
+
+/// closures can not be compared, so an `Aggregate` policy is never equal to another policy (not even to itself)
+impl<T> cmp::PartialEq for DuplicatePolicy<T> {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (DuplicatePolicy::Error, DuplicatePolicy::Error) | (DuplicatePolicy::KeepFirst, DuplicatePolicy::KeepFirst) | (DuplicatePolicy::KeepLast, DuplicatePolicy::KeepLast))
+    }
+}
+
+impl<T> fmt::Debug for DuplicatePolicy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DuplicatePolicy::Error => write!(f, "Error"),
+            DuplicatePolicy::KeepFirst => write!(f, "KeepFirst"),
+            DuplicatePolicy::KeepLast => write!(f, "KeepLast"),
+            DuplicatePolicy::Aggregate(_) => write!(f, "Aggregate(..)")
+        }
+    }
+}
 
 /// Timeseries base struct of an index and a Vec<T> of values
 #[derive(Clone,Debug)]
 pub struct TimeSeries<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> {
-    pub timeindicies: HashableIndex<TDate>,
-    pub values: Vec<T>, 
+    timeindicies: HashableIndex<TDate>,
+    values: Vec<T>, 
 }
 
 
@@ -38,7 +113,7 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
         TimeSeries::from_vecs(vec![], vec![]).unwrap()
     }
 
-    /// Create a series by giving a vector of indicies and values, this will error if the index is not unique or not monotonic or if the input arrays are not of equal length
+    /// Create a series by giving a vector of indicies and values, this will error if the index is not unique or not monotonic or if the input arrays are not of equal length. The error will contain the offending position in the index
     ///
     /// # Example
     ///
@@ -50,25 +125,16 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
     /// let ts = TimeSeries::from_vecs(index, vals).unwrap();
     /// assert_eq!(ts.len(), 5);
     /// ```
-    pub fn from_vecs(timeindicies: Vec<TDate>, values: Vec<T>) -> Result<TimeSeries<TDate, T>, std::io::Error> {
+    pub fn from_vecs(timeindicies: Vec<TDate>, values: Vec<T>) -> Result<TimeSeries<TDate, T>, TsxError> {
         let idx = HashableIndex::new(timeindicies);
-        if !idx.is_unique() || !idx.is_monotonic() {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "tied to build with an invalid index",
-            ))
-        } else {
-            TimeSeries::from_vecs_minimal_checks(idx, values)
-        }
+        idx.check_monotonic()?;
+        TimeSeries::from_vecs_minimal_checks(idx, values)
     }
 
     /// Create a series by giving a vector of indicies and values, it will check for the vectors being equal length
-    pub fn from_vecs_minimal_checks(timeindicies: HashableIndex<TDate>, values: Vec<T>) -> Result<TimeSeries<TDate, T>, std::io::Error> {
+    pub fn from_vecs_minimal_checks(timeindicies: HashableIndex<TDate>, values: Vec<T>) -> Result<TimeSeries<TDate, T>, TsxError> {
         if timeindicies.len() != values.len() {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "length mismatch",
-            ))
+            Err(TsxError::LengthMismatch{ index: timeindicies.len(), values: values.len() })
         } else {
             Ok(TimeSeries::from_vecs_unchecked(timeindicies, values))
 
@@ -98,7 +164,7 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
     /// let ts = TimeSeries::from_tsdatapoints(data);
     /// assert_eq!(ts.unwrap().len(), 5);
     /// ```
-    pub fn from_tsdatapoints(tsdatapoints: Vec<TimeSeriesDataPoint<TDate,T>>) -> Result<TimeSeries<TDate, T>, std::io::Error> {
+    pub fn from_tsdatapoints(tsdatapoints: Vec<TimeSeriesDataPoint<TDate,T>>) -> Result<TimeSeries<TDate, T>, TsxError> {
         let mut dpc = tsdatapoints;
         dpc.sort_by_key(|x| x.timestamp.clone());
         let len = dpc.len();
@@ -110,7 +176,7 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
         });
         TimeSeries::from_vecs(index,values)
     }
-    /// Create a new series from a set of TimeSeriesDataPoints without any ordering checks
+    /// Create a new series from a set of TimeSeriesDataPoints without any ordering checks, use `dedup_by` to resolve duplicate timestamps afterwards
     pub fn from_tsdatapoints_unchecked(tsdatapoints: Vec<TimeSeriesDataPoint<TDate,T>>) -> TimeSeries<TDate, T> {
         let len =  tsdatapoints.len();
         let mut index= Vec::with_capacity(len);
@@ -123,6 +189,76 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
         TimeSeries::from_vecs_unchecked(HashableIndex::new(index),values)
     }
 
+    /// Create a series by giving a vector of indicies and values, where points that share a timestamp are resolved with the duplicate policy. 
+    /// This will error if the index is decreasing somewhere, if the input arrays are not of equal length or on a duplicate with `DuplicatePolicy::Error`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::{TimeSeries,DuplicatePolicy};
+    ///
+    /// let ts = TimeSeries::from_vecs_with_policy(vec![1, 2, 2, 3], vec![1.0, 2.0, 4.0, 3.0], DuplicatePolicy::aggregate(|x| x.iter().sum())).unwrap();
+    /// assert_eq!(ts.values(), vec![1.0, 6.0, 3.0]);
+    /// ```
+    pub fn from_vecs_with_policy(timeindicies: Vec<TDate>, values: Vec<T>, policy: DuplicatePolicy<T>) -> Result<TimeSeries<TDate, T>, TsxError> {
+        if timeindicies.len() != values.len() {
+            return Err(TsxError::LengthMismatch{ index: timeindicies.len(), values: values.len() });
+        }
+        if let Some(pos) = (1..timeindicies.len()).find(|&pos| timeindicies[pos] < timeindicies[pos - 1]) {
+            return Err(TsxError::NonMonotonicIndex{ position: pos });
+        }
+        TimeSeries::from_sorted_with_policy(&timeindicies, &values, policy)
+    }
+
+    /// Create a new series from a set of TimeSeriesDataPoints, where points that share a timestamp are resolved with the duplicate policy. 
+    /// Your data is reordered into ascending order if needed, points with the same timestamp keep the order they came in
+    pub fn from_tsdatapoints_with_policy(tsdatapoints: Vec<TimeSeriesDataPoint<TDate,T>>, policy: DuplicatePolicy<T>) -> Result<TimeSeries<TDate, T>, TsxError> {
+        let mut dpc = tsdatapoints;
+        dpc.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        let (index, values): (Vec<TDate>, Vec<T>) = dpc.into_iter().map(|dp| (dp.timestamp, dp.value)).unzip();
+        TimeSeries::from_sorted_with_policy(&index, &values, policy)
+    }
+
+    /// Resolve the points that share a timestamp with the duplicate policy, i.e. for a series that was built with one of the unchecked constructors. 
+    /// The series is reordered into ascending order if needed, points with the same timestamp keep their order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::{TimeSeries,DuplicatePolicy};
+    /// use tsxlib::data_elements::TimeSeriesDataPoint;
+    ///
+    /// let ts = TimeSeries::from_tsdatapoints_unchecked(vec![TimeSeriesDataPoint::new(1, 1.0), TimeSeriesDataPoint::new(1, 2.0), TimeSeriesDataPoint::new(2, 3.0)]);
+    /// assert!(ts.dedup_by(DuplicatePolicy::Error).is_err());
+    /// assert_eq!(ts.dedup_by(DuplicatePolicy::KeepLast).unwrap().values(), vec![2.0, 3.0]);
+    /// ```
+    pub fn dedup_by(&self, policy: DuplicatePolicy<T>) -> Result<TimeSeries<TDate, T>, TsxError> {
+        match self.timeindicies.values().windows(2).all(|w| w[0] <= w[1]) {
+            true => TimeSeries::from_sorted_with_policy(self.timeindicies.values(), &self.values, policy),
+            false => TimeSeries::from_tsdatapoints_with_policy(self.into_iter().collect(), policy)
+        }
+    }
+
+    /// builds a series out of a non decreasing index, resolving every run of equal timestamps with the duplicate policy
+    fn from_sorted_with_policy(timeindicies: &[TDate], values: &[T], policy: DuplicatePolicy<T>) -> Result<TimeSeries<TDate, T>, TsxError> {
+        let mut index = Vec::with_capacity(timeindicies.len());
+        let mut deduped = Vec::with_capacity(values.len());
+        let mut start = 0;
+        while start < timeindicies.len() {
+            let end = start + timeindicies[start..].iter().take_while(|ts| **ts == timeindicies[start]).count();
+            let value = match (end - start, &policy) {
+                (1, _) | (_, DuplicatePolicy::KeepFirst) => values[start].clone(),
+                (_, DuplicatePolicy::Error) => return Err(TsxError::DuplicateTimestamp{ position: start + 1 }),
+                (_, DuplicatePolicy::KeepLast) => values[end - 1].clone(),
+                (_, DuplicatePolicy::Aggregate(agg_func)) => agg_func(&values[start..end])
+            };
+            index.push(timeindicies[start].clone());
+            deduped.push(value);
+            start = end;
+        }
+        Ok(TimeSeries::from_vecs_unchecked(HashableIndex::new(index), deduped))
+    }
+
     /// Get the length of a series
     ///
     /// # Example
@@ -144,6 +280,175 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
         self.timeindicies.is_empty()
     }
 
+    /// The index of the series
+    pub fn index(&self) -> &HashableIndex<TDate> {
+        &self.timeindicies
+    }
+
+    /// The values of the series, in index order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 3], vec![1.0, 2.0, 3.0]).unwrap();
+    /// assert_eq!(ts.values(), &[1.0, 2.0, 3.0]);
+    /// assert_eq!(ts.index().values(), &[1, 2, 3]);
+    /// ```
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// The values of the series for in place updates, the index can not be changed this way
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// The keys and the values of the series as two slices of the same length
+    pub fn as_slices(&self) -> (&[TDate], &[T]) {
+        (self.timeindicies.values(), &self.values)
+    }
+
+    /// Take the series apart into its index and its values
+    pub fn into_parts(self) -> (HashableIndex<TDate>, Vec<T>) {
+        (self.timeindicies, self.values)
+    }
+
+    /// Checks that the index is sorted, unique and as long as the values. This is only done in debug builds, it runs after every mutating operation
+    #[inline]
+    fn debug_check_invariants(&self) {
+        debug_assert_eq!(self.timeindicies.len(), self.values.len(), "the index and the values of a series have to be of the same length");
+        debug_assert!(self.timeindicies.check_monotonic().is_ok(), "the index of a series has to be sorted and unique");
+    }
+
+    /// Append a point that is newer than the last point of the series, errors (leaving the series untouched) if it is not
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    ///
+    /// let mut ts = TimeSeries::from_vecs(vec![1, 2], vec![1.0, 2.0]).unwrap();
+    /// assert!(ts.push(3, 3.0).is_ok());
+    /// assert!(ts.push(3, 4.0).is_err());
+    /// assert_eq!(ts.len(), 3);
+    /// ```
+    pub fn push(&mut self, timestamp: TDate, value: T) -> Result<(), TsxError> {
+        if let Some(last) = self.timeindicies.last() {
+            match timestamp.cmp(last) {
+                cmp::Ordering::Less => return Err(TsxError::NonMonotonicIndex{ position: self.len() }),
+                cmp::Ordering::Equal => return Err(TsxError::DuplicateTimestamp{ position: self.len() }),
+                cmp::Ordering::Greater => {}
+            }
+        }
+        self.timeindicies.values_mut().push(timestamp);
+        self.values.push(value);
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Insert a point at its place in the series, if the timestamp is already in the series the duplicate policy decides which value is kept
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::{TimeSeries,DuplicatePolicy};
+    ///
+    /// let mut ts = TimeSeries::from_vecs(vec![1, 3], vec![1.0, 3.0]).unwrap();
+    /// ts.insert(2, 2.0, DuplicatePolicy::Error).unwrap();
+    /// ts.insert(3, 4.0, DuplicatePolicy::KeepLast).unwrap();
+    /// assert_eq!(ts.values(), vec![1.0, 2.0, 4.0]);
+    /// ```
+    pub fn insert(&mut self, timestamp: TDate, value: T, policy: DuplicatePolicy<T>) -> Result<(), TsxError> {
+        match self.timeindicies.values().binary_search(&timestamp) {
+            Ok(pos) => match policy {
+                DuplicatePolicy::Error => return Err(TsxError::DuplicateTimestamp{ position: pos }),
+                DuplicatePolicy::KeepFirst => {},
+                DuplicatePolicy::KeepLast => self.values[pos] = value,
+                DuplicatePolicy::Aggregate(agg_func) => self.values[pos] = agg_func(&[self.values[pos].clone(), value])
+            },
+            Err(pos) => {
+                self.timeindicies.values_mut().insert(pos, timestamp);
+                self.values.insert(pos, value);
+            }
+        }
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Append sorted points that are all newer than the last point of the series, errors (leaving the series untouched) if they are not. The error position is the position the point would have had in the series
+    pub fn extend<I>(&mut self, points: I) -> Result<(), TsxError>
+    where
+        I: IntoIterator<Item = TimeSeriesDataPoint<TDate,T>>
+    {
+        let points: Vec<TimeSeriesDataPoint<TDate,T>> = points.into_iter().collect();
+        let mut last = self.timeindicies.last();
+        for (offset, dp) in points.iter().enumerate() {
+            match last.map(|l| dp.timestamp.cmp(l)) {
+                Some(cmp::Ordering::Less) => return Err(TsxError::NonMonotonicIndex{ position: self.len() + offset }),
+                Some(cmp::Ordering::Equal) => return Err(TsxError::DuplicateTimestamp{ position: self.len() + offset }),
+                _ => last = Some(&dp.timestamp)
+            }
+        }
+        self.timeindicies.values_mut().reserve(points.len());
+        self.values.reserve(points.len());
+        for dp in points {
+            self.timeindicies.values_mut().push(dp.timestamp);
+            self.values.push(dp.value);
+        }
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Remove the point at the timestamp, returns its value or None if the timestamp is not in the series
+    pub fn remove(&mut self, timestamp: &TDate) -> Option<T> {
+        let pos = self.timeindicies.values().binary_search(timestamp).ok()?;
+        self.timeindicies.values_mut().remove(pos);
+        let value = self.values.remove(pos);
+        self.debug_check_invariants();
+        Some(value)
+    }
+
+    /// Drop all points before the timestamp, the point at the timestamp is kept
+    pub fn truncate_before(&mut self, timestamp: &TDate) {
+        let pos = self.timeindicies.values().partition_point(|ts| ts < timestamp);
+        self.timeindicies.values_mut().drain(..pos);
+        self.values.drain(..pos);
+        self.debug_check_invariants();
+    }
+
+    /// Drop all points after the timestamp, the point at the timestamp is kept
+    pub fn truncate_after(&mut self, timestamp: &TDate) {
+        let pos = self.timeindicies.values().partition_point(|ts| ts <= timestamp);
+        self.timeindicies.values_mut().truncate(pos);
+        self.values.truncate(pos);
+        self.debug_check_invariants();
+    }
+
+    /// Keep only the points for which the predicate returns true
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    ///
+    /// let mut ts = TimeSeries::from_vecs(vec![1, 2, 3, 4], vec![1.0, -2.0, 3.0, -4.0]).unwrap();
+    /// ts.retain(|_, v| *v > 0.0);
+    /// assert_eq!(ts.index().values(), vec![1, 3]);
+    /// ```
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&TDate, &T) -> bool
+    {
+        let keep: Vec<bool> = self.timeindicies.iter().zip(self.values.iter()).map(|(ts, v)| predicate(ts, v)).collect();
+        let mut keep_index = keep.iter();
+        self.timeindicies.values_mut().retain(|_| *keep_index.next().unwrap());
+        let mut keep_values = keep.iter();
+        self.values.retain(|_| *keep_values.next().unwrap());
+        self.debug_check_invariants();
+    }
+
     /// index into the series by position, returns None if not found
     ///
     /// # Example
@@ -184,13 +489,13 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
     /// ```
 
     pub fn at(&self, timestamp: TDate) -> Option<T> {
-        match self.timeindicies.values.binary_search(&timestamp) {
+        match self.timeindicies.values().binary_search(&timestamp) {
             Ok(pos) => Some(self.values[pos].clone()),
             Err(_pos) => None
         }
     }
 
-    /// Return element by its timestamp index or the first prior if out of range return none
+    /// Return element by its timestamp index or the first prior if out of range return none. A timestamp is out of range when it is before the first or after the last key of the series, `asof_many` follows the same rule
     ///
     /// # Example
     ///
@@ -208,22 +513,109 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
     /// assert_eq!(ts.at_or_first_prior(NaiveDateTime::from_timestamp(20,0)), None);
     /// ```
     pub fn at_or_first_prior(&self, timestamp: TDate) -> Option<T> {
-        let maxts = self.timeindicies.last();
-        let pos = match self.timeindicies.iter().position(|ts| timestamp < *ts) {
-            Some(idx) => idx, // timespan out of range on the other end
-            _ => {
-                if maxts.is_some() && timestamp > *(maxts.unwrap()) {
-                    0
-                } else {
-                    self.len()
+        match self.timeindicies.last() {
+            Some(last) if timestamp <= *last => {
+                let pos = self.timeindicies.values().partition_point(|ts| *ts <= timestamp);
+                pos.checked_sub(1).map(|pos| self.values[pos].clone())
+            },
+            _ => None // timespan out of range on the other end
+        }
+    }
+
+    /// Return element by its timestamp index or the first after, returns none only if the timestamp is after the last key
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 5, 10], vec![1.0, 2.0, 3.0]).unwrap();
+    /// assert_eq!(ts.at_or_first_after(0), Some(1.0));
+    /// assert_eq!(ts.at_or_first_after(1), Some(1.0));
+    /// assert_eq!(ts.at_or_first_after(4), Some(2.0));
+    /// assert_eq!(ts.at_or_first_after(20), None);
+    /// ```
+    pub fn at_or_first_after(&self, timestamp: TDate) -> Option<T> {
+        let pos = self.timeindicies.values().partition_point(|ts| *ts < timestamp);
+        self.values.get(pos).cloned()
+    }
+
+    /// Return the element with the timestamp closest to the given one, ties go to the prior element. Returns None only for an empty series
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 5, 10], vec![1.0, 2.0, 3.0]).unwrap();
+    /// assert_eq!(ts.nearest(-5), Some(1.0));
+    /// assert_eq!(ts.nearest(3), Some(1.0));
+    /// assert_eq!(ts.nearest(8), Some(3.0));
+    /// assert_eq!(ts.nearest(20), Some(3.0));
+    /// ```
+    pub fn nearest(&self, timestamp: TDate) -> Option<T>
+    where
+        TDate: TimeDistance
+    {
+        let pos = self.timeindicies.values().partition_point(|ts| *ts < timestamp);
+        let nearest = match (pos.checked_sub(1), self.timeindicies.values().get(pos)) {
+            (Some(prior), Some(next)) if next.delta(&timestamp) < timestamp.delta(&self.timeindicies[prior]) => pos,
+            (Some(prior), _) => prior,
+            (None, _) => pos
+        };
+        self.values.get(nearest).cloned()
+    }
+
+    /// for every query the number of keys smaller than it, sorted queries are merged with the index in a single pass while a query that goes back is looked up with a binary search
+    fn merge_positions(&self, timestamps: &[TDate]) -> Vec<usize> {
+        let index = self.timeindicies.values();
+        let mut pos = 0;
+        let mut prior: Option<&TDate> = None;
+        timestamps.iter().map(|timestamp| {
+            match prior {
+                Some(p) if timestamp < p => pos = index.partition_point(|ts| ts < timestamp),
+                _ => while pos < index.len() && index[pos] < *timestamp {
+                    pos += 1;
                 }
             }
-        };
-        if pos > 0 {
-            Some(self.values[pos - 1].clone())
-        } else {
-            None
-        }
+            prior = Some(timestamp);
+            pos
+        }).collect()
+    }
+
+    /// Return the elements at the given timestamps, or None where a timestamp is not in the series. Sorted timestamps are looked up in a single merge pass
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 5, 10], vec![1.0, 2.0, 3.0]).unwrap();
+    /// assert_eq!(ts.at_many(&[1, 4, 10]), vec![Some(1.0), None, Some(3.0)]);
+    /// ```
+    pub fn at_many(&self, timestamps: &[TDate]) -> Vec<Option<T>> {
+        self.merge_positions(timestamps).into_iter().zip(timestamps.iter()).map(|(pos, timestamp)| match self.timeindicies.values().get(pos) {
+            Some(ts) if ts == timestamp => Some(self.values[pos].clone()),
+            _ => None
+        }).collect()
+    }
+
+    /// Return `at_or_first_prior` for each of the given timestamps. Sorted timestamps are looked up in a single merge pass
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 5, 10], vec![1.0, 2.0, 3.0]).unwrap();
+    /// assert_eq!(ts.asof_many(&[0, 1, 7, 10, 20]), vec![None, Some(1.0), Some(2.0), Some(3.0), None]);
+    /// ```
+    pub fn asof_many(&self, timestamps: &[TDate]) -> Vec<Option<T>> {
+        self.merge_positions(timestamps).into_iter().zip(timestamps.iter()).map(|(pos, timestamp)| match self.timeindicies.values().get(pos) {
+            Some(ts) if ts == timestamp => Some(self.values[pos].clone()),
+            Some(_) => pos.checked_sub(1).map(|prior| self.values[prior].clone()),
+            None => None // timespan out of range on the other end
+        }).collect()
     }
 
 
@@ -337,8 +729,11 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
     /// let ts_expected = TimeSeries::from_tsdatapoints(expected).unwrap();
     /// assert_eq!(ts_rounded_up, ts_expected);
     /// ```
-    pub fn resample_and_agg<TRes,TDuration>(&self, sample_size :TDuration, group_func: fn(&TDate,&TDuration)->TDate, agg_func: fn(&Vec<TimeSeriesDataPoint<&TDate,&T>>)->TRes ) -> TimeSeries<TDate,TRes>
-    where TRes : Copy
+    pub fn resample_and_agg<TRes,TDuration,FGroup,FAgg>(&self, sample_size :TDuration, mut group_func: FGroup, mut agg_func: FAgg) -> TimeSeries<TDate,TRes>
+    where 
+        TRes : Copy,
+        FGroup : FnMut(&TDate,&TDuration)->TDate,
+        FAgg : FnMut(&Vec<TimeSeriesDataPoint<&TDate,&T>>)->TRes
     {
         // let mut groupmap: HashMap<TDate, Vec<TimeSeriesDataPoint<TDate,T>>> = HashMap::with_capacity(self.len());  
         // self.iter().for_each(|dp| {
@@ -351,6 +746,242 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
         self.iter().group_by(|dp| group_func(&dp.timestamp,&sample_size)).into_iter().map(|grp|  TimeSeriesDataPoint::new(grp.0, agg_func(&grp.1.collect()))).collect_from_unchecked_iter()
     }
 
+    /// Resample a Timeseries onto a regular grid of `sample_size` wide bins, aggregating the points of each bin with `agg_func`.
+    /// Unlike `resample_and_agg` the grid is explicit, so with `FillEmpty::Null` or `FillEmpty::ForwardFill` every bin between the first and the last point is reported.
+    /// Returns an error if `sample_size` is not positive, bin labels past the bounds of the key type saturate
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::{TimeSeries,ResampleOptions,BinEdge,FillEmpty};
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![0i64, 3, 5, 21], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    /// let options = ResampleOptions { label: BinEdge::Right, fill_empty: FillEmpty::Null, ..Default::default() };
+    /// let tsres = ts.resample(5, options, |x| x.iter().map(|dp| *dp.value).sum::<f64>()).unwrap();
+    /// assert_eq!(tsres.index().values(), vec![5, 10, 15, 20, 25]);
+    /// assert_eq!(tsres.values(), vec![Some(3.0), Some(3.0), None, None, Some(4.0)]);
+    /// ```
+    pub fn resample<TRes,FAgg>(&self, sample_size: TDate::Delta, options: ResampleOptions<TDate>, mut agg_func: FAgg) -> Result<TimeSeries<TDate,Option<TRes>>, TsxError>
+    where 
+        TDate: TimeStep,
+        TRes : Clone,
+        FAgg : FnMut(&[TimeSeriesDataPoint<&TDate,&T>])->TRes
+    {
+        if sample_size <= TDate::zero_delta() {
+            return Err(TsxError::InvalidParameter{ name: "sample size", value: format!("{:?}", sample_size), expected: "> 0" });
+        }
+        if self.is_empty() {
+            return Ok(TimeSeries::empty());
+        }
+        let origin = match &options.origin {
+            ResampleOrigin::Start => self.timeindicies[0].clone(),
+            ResampleOrigin::At(origin) => origin.clone()
+        }.add_delta(&options.offset);
+        let bin_of = |stamp: &TDate| {
+            let steps = TDate::steps_in(&stamp.delta(&origin), &sample_size);
+            match options.closed {
+                BinEdge::Right if origin.add_delta(&TDate::scale_delta(&sample_size, steps)) == *stamp => steps - 1,
+                _ => steps
+            }
+        };
+        let label_of = |bin: i64| match options.label {
+            BinEdge::Left => origin.add_delta(&TDate::scale_delta(&sample_size, bin)),
+            BinEdge::Right => origin.add_delta(&TDate::scale_delta(&sample_size, bin + 1))
+        };
+
+        let mut index: Vec<TDate> = Vec::new();
+        let mut values: Vec<Option<TRes>> = Vec::new();
+        let mut last_bin: Option<i64> = None;
+        let mut group: Vec<TimeSeriesDataPoint<&TDate,&T>> = Vec::new();
+        // note that this relies on the series being ordered, the bins are then visited in order too
+        for (pos, dp) in self.iter().enumerate() {
+            let bin = bin_of(dp.timestamp);
+            group.push(dp);
+            let closes_bin = match self.timeindicies.values().get(pos + 1) {
+                Some(next) => bin_of(next) != bin,
+                None => true
+            };
+            if !closes_bin {
+                continue;
+            }
+            if let Some(prior) = last_bin {
+                for empty in prior + 1..bin {
+                    let fill = match options.fill_empty {
+                        FillEmpty::Skip => continue,
+                        FillEmpty::Null => None,
+                        FillEmpty::ForwardFill => values.last().cloned().flatten()
+                    };
+                    index.push(label_of(empty));
+                    values.push(fill);
+                }
+            }
+            index.push(label_of(bin));
+            values.push(Some(agg_func(&group)));
+            group.clear();
+            last_bin = Some(bin);
+        }
+        Ok(TimeSeries::from_vecs_unchecked(HashableIndex::new(index), values))
+    }
+
+    /// Conform the series to the given index, keys that are not in the series are filled according to the fill policy or set to None. 
+    /// The series and the index are merge joined so this is linear in their lengths
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::{TimeSeries,FillPolicy};
+    /// use tsxlib::index::HashableIndex;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![2, 4, 8], vec![1.0, 2.0, 4.0]).unwrap();
+    /// let index = HashableIndex::new(vec![1, 2, 3, 6, 10]);
+    /// let tsres = ts.reindex(&index, FillPolicy::ForwardFill{ limit: None });
+    /// assert_eq!(tsres.values(), vec![None, Some(1.0), Some(1.0), Some(2.0), Some(4.0)]);
+    /// ```
+    pub fn reindex(&self, index: &HashableIndex<TDate>, policy: FillPolicy<TDate::Delta>) -> TimeSeries<TDate,Option<T>>
+    where 
+        TDate: TimeDistance
+    {
+        let je = JoinEngine{idx_this : index, idx_other : &self.timeindicies};
+        let brackets = je.get_bracketing_merge_joined_indicies();
+        let is_exact = |bracket: &IndexJoinBracket| bracket.prior_idx.is_some() && bracket.prior_idx == bracket.next_idx;
+        let values: Vec<Option<T>> = match policy {
+            FillPolicy::None => brackets.iter().map(|bracket| match is_exact(bracket) {
+                true => Some(self.values[bracket.prior_idx.unwrap()].clone()),
+                false => None
+            }).collect(),
+            FillPolicy::ForwardFill{ limit } => self.fill_limited(brackets.iter(), |bracket| bracket.prior_idx, limit),
+            FillPolicy::BackwardFill{ limit } => {
+                let mut values = self.fill_limited(brackets.iter().rev(), |bracket| bracket.next_idx, limit);
+                values.reverse();
+                values
+            },
+            FillPolicy::Nearest{ tolerance } => brackets.iter().map(|bracket| {
+                let key = &index[bracket.this_idx];
+                let distance = |idx: usize| self.timeindicies[idx].delta(key).max(key.delta(&self.timeindicies[idx]));
+                let nearest = match (bracket.prior_idx, bracket.next_idx) {
+                    (Some(prior), Some(next)) if distance(next) < distance(prior) => Some(next),
+                    (Some(prior), _) => Some(prior),
+                    (None, next) => next
+                };
+                match (nearest, tolerance) {
+                    (Some(idx), Some(tol)) if distance(idx) > tol => None,
+                    (nearest, _) => nearest.map(|idx| self.values[idx].clone())
+                }
+            }).collect()
+        };
+        TimeSeries::from_vecs_unchecked(index.clone(), values)
+    }
+
+    /// Conform the series to the given index like `reindex`, keys between two points of the series are interpolated linearly in the distance between the keys and keys outside of the series are set to None
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use tsxlib::index::HashableIndex;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![2, 4, 8], vec![1.0, 2.0, 4.0]).unwrap();
+    /// let index = HashableIndex::new(vec![1, 2, 3, 6, 10]);
+    /// let tsres = ts.reindex_interpolated(&index);
+    /// assert_eq!(tsres.values(), vec![None, Some(1.0), Some(1.5), Some(3.0), None]);
+    /// ```
+    pub fn reindex_interpolated(&self, index: &HashableIndex<TDate>) -> TimeSeries<TDate,Option<T>>
+    where 
+        TDate: TimeStep,
+        T: Interpolate
+    {
+        let je = JoinEngine{idx_this : index, idx_other : &self.timeindicies};
+        let values = je.get_bracketing_merge_joined_indicies().iter().map(|bracket| match (bracket.prior_idx, bracket.next_idx) {
+            (Some(prior), Some(next)) if prior == next => Some(self.values[prior].clone()),
+            (Some(prior), Some(next)) => {
+                let (start, end) = (&self.timeindicies[prior], &self.timeindicies[next]);
+                let weight = TDate::delta_ratio(&index[bracket.this_idx].delta(start), &end.delta(start));
+                Some(self.values[prior].interpolate(&self.values[next], weight))
+            },
+            _ => None
+        }).collect();
+        TimeSeries::from_vecs_unchecked(index.clone(), values)
+    }
+
+    /// fills from the point picked by `source_func`, where exact matches do not count towards the limit
+    fn fill_limited<'a, I, F>(&self, brackets: I, source_func: F, limit: Option<usize>) -> Vec<Option<T>>
+    where 
+        I: Iterator<Item = &'a IndexJoinBracket>,
+        F: Fn(&IndexJoinBracket)->Option<usize>
+    {
+        let mut last_source: Option<usize> = None;
+        let mut filled = 0;
+        brackets.map(|bracket| {
+            let source = source_func(bracket)?;
+            if bracket.prior_idx == bracket.next_idx {
+                return Some(self.values[source].clone());
+            }
+            filled = match last_source == Some(source) {
+                true => filled + 1,
+                false => 1
+            };
+            last_source = Some(source);
+            match limit {
+                Some(lim) if filled > lim => None,
+                _ => Some(self.values[source].clone())
+            }
+        }).collect()
+    }
+
+    /// Conform the series to a regular grid of `freq` spaced keys from its first to its last key, see `reindex`. Returns an error if `freq` is not positive
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::{TimeSeries,FillPolicy};
+    /// use tsxlib::index::HashableIndex;
+    /// use chrono::Duration;
+    ///
+    /// let index = HashableIndex::from_int_stamps(vec![0, 180]).into_values();
+    /// let ts = TimeSeries::from_vecs(index, vec![1.0, 4.0]).unwrap();
+    /// let tsres = ts.upsample(Duration::minutes(1), FillPolicy::ForwardFill{ limit: None }).unwrap();
+    /// assert_eq!(tsres.index(), &HashableIndex::from_int_stamps(vec![0, 60, 120, 180]));
+    /// assert_eq!(tsres.values(), vec![Some(1.0), Some(1.0), Some(1.0), Some(4.0)]);
+    /// ```
+    pub fn upsample(&self, freq: TDate::Delta, policy: FillPolicy<TDate::Delta>) -> Result<TimeSeries<TDate,Option<T>>, TsxError>
+    where 
+        TDate: TimeStep
+    {
+        Ok(self.reindex(&self.upsample_grid(freq)?, policy))
+    }
+
+    /// Conform the series to a regular grid of `freq` spaced keys from its first to its last key, interpolating between the points, see `reindex_interpolated`. Returns an error if `freq` is not positive
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use tsxlib::index::HashableIndex;
+    /// use chrono::Duration;
+    ///
+    /// let index = HashableIndex::from_int_stamps(vec![0, 180]).into_values();
+    /// let ts = TimeSeries::from_vecs(index, vec![1.0, 4.0]).unwrap();
+    /// let tsres = ts.upsample_interpolated(Duration::minutes(1)).unwrap();
+    /// assert_eq!(tsres.values(), vec![Some(1.0), Some(2.0), Some(3.0), Some(4.0)]);
+    /// ```
+    pub fn upsample_interpolated(&self, freq: TDate::Delta) -> Result<TimeSeries<TDate,Option<T>>, TsxError>
+    where 
+        TDate: TimeStep,
+        T: Interpolate
+    {
+        Ok(self.reindex_interpolated(&self.upsample_grid(freq)?))
+    }
+
+    fn upsample_grid(&self, freq: TDate::Delta) -> Result<HashableIndex<TDate>, TsxError>
+    where 
+        TDate: TimeStep
+    {
+        match (self.timeindicies.values().first(), self.timeindicies.last()) {
+            (Some(first), Some(last)) => HashableIndex::date_range(first.clone(), last.clone(), freq),
+            _ => crate::index::check_step::<TDate>(freq).map(|_| HashableIndex::new(vec![]))
+        }
+    }
+
     /// Shift a series by a given index, i.e. a "shift" of -1 will lag the series by 1 obs while a "shift" of 1 will nudge it fwd by 1
     ///
     /// # Example
@@ -369,20 +1000,188 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
         ShiftedTimeSeriesIter::new(&self, 0, shift)
     }
 
-    /// Apply a rolling function on the values of timeseries via a buffer, this is the less efficient cousin of `apply_updating_rolling`
-    pub fn apply_rolling<TRes>(&self, window_size: usize,transform_func: fn(&Vec<T>)->TRes) -> RollingTimeSeriesIter<TDate,T, TRes>
-    where TRes : Clone
+    /// Apply a rolling function on the values of timeseries via a buffer, this is the less efficient cousin of `apply_updating_rolling`. Only full windows produce a value, so a window larger than the series gives nothing
+    pub fn apply_rolling<TRes,F>(&self, window_size: usize,transform_func: F) -> RollingTimeSeriesIter<TDate,T, TRes, F>
+    where 
+        TRes : Clone,
+        F : FnMut(&Vec<T>)->TRes
     {
         RollingTimeSeriesIter::new(&self, window_size, transform_func)
     }
 
     /// Apply a rolling function on the values of timeseries via a update and decrement functions, this is the more efficient cousin of `apply_rolling`
-    pub fn apply_updating_rolling<TRes>(&self, window_size: usize,update_func: fn(Option<TRes>, &T)->Option<TRes>, decrement_func: fn(Option<TRes>, &T)->Option<TRes>) -> RollingTimeSeriesIterWithUpdate<TDate,T, TRes>
-    where TRes : Clone
+    pub fn apply_updating_rolling<TRes,FUpdate,FDecrement>(&self, window_size: usize,update_func: FUpdate, decrement_func: FDecrement) -> RollingTimeSeriesIterWithUpdate<TDate,T, TRes, FUpdate, FDecrement>
+    where 
+        TRes : Clone,
+        FUpdate : FnMut(Option<TRes>, &T)->Option<TRes>,
+        FDecrement : FnMut(Option<TRes>, &T)->Option<TRes>
     {
         RollingTimeSeriesIterWithUpdate::new(&self, window_size, update_func, decrement_func)
     }
 
+    /// Apply a rolling function on the values of timeseries with a trailing or centered window, that can also produce values for the partial windows at the edges of the series. 
+    /// Every point is reported with `Some` value, or with None if its window has less than `min_periods` points and `placeholders` is set
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use tsxlib::timeseries_iterators::RollingOptions;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    /// let options = RollingOptions { center: true, placeholders: true, ..Default::default() };
+    /// let result: TimeSeries<i32,Option<f64>> = ts.apply_rolling_with(3, options, |buffer| buffer.iter().sum()).collect();
+    /// assert_eq!(result.values(), vec![None, Some(6.0), Some(9.0), None]);
+    /// ```
+    pub fn apply_rolling_with<TRes,F>(&self, window_size: usize, options: RollingOptions, transform_func: F) -> AlignedRollingTimeSeriesIter<'_,TDate,T, TRes, F>
+    where 
+        TRes : Clone,
+        F : FnMut(&[T])->TRes
+    {
+        AlignedRollingTimeSeriesIter::new(self, window_size, options, transform_func)
+    }
+
+    /// Apply a rolling function over a time span rather than a number of observations, i.e. "the last 5 minutes". `closed` sets which ends of the window are included and points whose window has less than `min_observations` points are skipped
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use tsxlib::timeseries_iterators::WindowClosed;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![0i64, 1, 2, 5, 6, 10], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    /// let rolled: TimeSeries<i64,f64> = ts.apply_rolling_by_time(3, WindowClosed::Right, 1, |buffer| buffer.iter().sum()).collect();
+    /// assert_eq!(rolled.values(), vec![1.0, 3.0, 6.0, 4.0, 9.0, 6.0]);
+    /// ```
+    pub fn apply_rolling_by_time<TRes,F>(&self, span: TDate::Delta, closed: WindowClosed, min_observations: usize, transform_func: F) -> TimeRollingTimeSeriesIter<'_,TDate,T, TRes, F>
+    where 
+        TDate : TimeDistance,
+        TRes : Clone,
+        F : FnMut(&[T])->TRes
+    {
+        TimeRollingTimeSeriesIter::new(self, span, closed, min_observations, transform_func)
+    }
+
+    /// Apply a rolling function over a time span via a update and decrement functions, this is the more efficient cousin of `apply_rolling_by_time`
+    pub fn apply_updating_rolling_by_time<TRes,FUpdate,FDecrement>(&self, span: TDate::Delta, closed: WindowClosed, min_observations: usize, update_func: FUpdate, decrement_func: FDecrement) -> TimeRollingTimeSeriesIterWithUpdate<'_,TDate,T, TRes, FUpdate, FDecrement>
+    where 
+        TDate : TimeDistance,
+        TRes : Clone,
+        FUpdate : FnMut(Option<TRes>, &T)->Option<TRes>,
+        FDecrement : FnMut(Option<TRes>, &T)->Option<TRes>
+    {
+        TimeRollingTimeSeriesIterWithUpdate::new(self, span, closed, min_observations, update_func, decrement_func)
+    }
+
+    /// Apply an expanding (cumulative) function on the values of timeseries, the update function is given the running state and the next value and the iterator yields the state at every point for which it is not None
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    /// let result: TimeSeries<i32,f64> = ts.apply_expanding(|acc: Option<f64>, x| Some(acc.unwrap_or(0.0) + x)).collect();
+    /// assert_eq!(result.values(), vec![1.0, 3.0, 6.0, 10.0]);
+    /// ```
+    pub fn apply_expanding<TRes,F>(&self, update_func: F) -> ExpandingTimeSeriesIter<'_,TDate,T, TRes, F>
+    where 
+        TRes : Clone,
+        F : FnMut(Option<TRes>, &T)->Option<TRes>
+    {
+        ExpandingTimeSeriesIter::new(self, update_func)
+    }
+
+    /// Cumulative sum of the values of the series
+    pub fn cumsum(&self) -> TimeSeries<TDate,T>
+    where 
+        T : Copy + std::ops::Add<Output = T>
+    {
+        self.apply_expanding(|acc: Option<T>, x: &T| Some(acc.map_or(*x, |a| a + *x))).collect_from_unchecked_iter()
+    }
+
+    /// Cumulative product of the values of the series
+    pub fn cumprod(&self) -> TimeSeries<TDate,T>
+    where 
+        T : Copy + std::ops::Mul<Output = T>
+    {
+        self.apply_expanding(|acc: Option<T>, x: &T| Some(acc.map_or(*x, |a| a * *x))).collect_from_unchecked_iter()
+    }
+
+    /// Cumulative maximum of the values of the series
+    pub fn cummax(&self) -> TimeSeries<TDate,T>
+    where 
+        T : Copy + cmp::PartialOrd
+    {
+        self.apply_expanding(|acc: Option<T>, x: &T| Some(match acc {
+            Some(a) if x.partial_cmp(&a) != Some(cmp::Ordering::Greater) => a,
+            _ => *x
+        })).collect_from_unchecked_iter()
+    }
+
+    /// Cumulative minimum of the values of the series
+    pub fn cummin(&self) -> TimeSeries<TDate,T>
+    where 
+        T : Copy + cmp::PartialOrd
+    {
+        self.apply_expanding(|acc: Option<T>, x: &T| Some(match acc {
+            Some(a) if x.partial_cmp(&a) != Some(cmp::Ordering::Less) => a,
+            _ => *x
+        })).collect_from_unchecked_iter()
+    }
+
+    /// Exponentially weighted mean and variance of the series, with `adjust` the weights are normalized over all prior observations (like pandas), otherwise the recursive definition is used
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use tsxlib::timeseries_iterators::EwmDecay;
+    /// use tsxlib::data_elements::TimeSeriesDataPoint;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 3], vec![1.0, 2.0, 3.0]).unwrap();
+    /// let result: TimeSeries<i32,f64> = ts.ewm(EwmDecay::alpha(0.5).unwrap(), false).map(|dp| TimeSeriesDataPoint::new(dp.timestamp, dp.value.mean)).collect();
+    /// assert_eq!(result.values(), vec![1.0, 1.5, 2.25]);
+    /// ```
+    pub fn ewm(&self, decay: EwmDecay, adjust: bool) -> EwmTimeSeriesIter<'_,TDate,T,impl FnMut(&TDate,&TDate)->f64>
+    where 
+        T : Into<f64>
+    {
+        let factor = 1.0 - decay.smoothing_factor();
+        EwmTimeSeriesIter::new(self, adjust, move |_: &TDate, _: &TDate| factor)
+    }
+
+    /// Exponentially weighted mean and variance of an irregularly sampled series, the weights decay by elapsed time instead of by number of observations, i.e. a weight halves every `halflife`.
+    /// Returns an error if `halflife` is not positive
+    #[allow(clippy::type_complexity)]
+    pub fn ewm_by_time(&self, halflife: chrono::Duration, adjust: bool) -> Result<EwmTimeSeriesIter<'_,TDate,T,impl FnMut(&TDate,&TDate)->f64>, TsxError>
+    where 
+        TDate : TimeDistance<Delta = chrono::Duration>,
+        T : Into<f64>
+    {
+        let halflife_secs = timeutils::duration_as_secs_f64(&halflife);
+        if halflife_secs <= 0.0 {
+            return Err(TsxError::InvalidParameter{ name: "halflife", value: halflife.to_string(), expected: "> 0" });
+        }
+        Ok(EwmTimeSeriesIter::new(self, adjust, move |current: &TDate, prior: &TDate| 0.5f64.powf(timeutils::duration_as_secs_f64(&current.delta(prior)) / halflife_secs)))
+    }
+
+    /// Exponentially weighted covariance of two series over their common timestamps
+    pub fn ewm_cov<T2>(&self, other: &TimeSeries<TDate,T2>, decay: EwmDecay, adjust: bool, bias: bool) -> TimeSeries<TDate,f64>
+    where 
+        T : Into<f64>,
+        T2 : Clone + Into<f64>
+    {
+        let factor = 1.0 - decay.smoothing_factor();
+        let joined = self.cross_apply_inner(other, |a: &T, b: &T2| (a.clone().into(), b.clone().into()));
+        let mut state = EwmState::new(adjust);
+        let values = joined.values.iter().enumerate().map(|(pos, (x, y))| {
+            state.update(if pos == 0 { 1.0 } else { factor }, *x, *y);
+            state.cov(bias)
+        }).collect();
+        TimeSeries::from_vecs_unchecked(joined.timeindicies, values)
+    }
+
     /// Map the desired UDF over elements of a series
     ///
     /// # Example
@@ -397,8 +1196,10 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
     /// let result = ts.map(|x| x * 2.0);
     /// assert_eq!(result.len(), 5);
     /// ```
-    pub fn map<TRes>(&self, func: fn(&T)->TRes) ->  TimeSeries<TDate,TRes>
-    where TRes : Clone + Default
+    pub fn map<TRes,F>(&self, func: F) ->  TimeSeries<TDate,TRes>
+    where 
+        TRes : Clone + Default,
+        F : Fn(&T)->TRes
     { #![allow(clippy::needless_range_loop)]
         let mut newvals:Vec<TRes> = Vec::with_capacity(self.values.len());
         newvals.resize_with(self.values.len(), Default::default);
@@ -422,8 +1223,10 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
     /// let result = ts.map_with_date(|_dt,x| x * 2.0);
     /// assert_eq!(result.len(), 5);
     /// ```
-    pub fn map_with_date<TRes>(&self, func: fn(&TDate,&T)->TRes) ->  TimeSeries<TDate,TRes>
-    where TRes : Clone + Default
+    pub fn map_with_date<TRes,F>(&self, func: F) ->  TimeSeries<TDate,TRes>
+    where 
+        TRes : Clone + Default,
+        F : Fn(&TDate,&T)->TRes
     { #![allow(clippy::needless_range_loop)]
         let mut newvals:Vec<TRes> = Vec::with_capacity(self.values.len());
         newvals.resize_with(self.values.len(), Default::default);
@@ -432,67 +1235,244 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
         }
         TimeSeries::from_vecs_unchecked(self.timeindicies.clone(), newvals)
     }
-    /// Apply a function that calculates its resultant value based on the begining and end of the specified skip span
+    /// Apply a function that calculates its resultant value based on the begining and end of the specified skip span
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use chrono::NaiveDateTime;
+    ///
+    /// let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+    /// let index = (0..values.len()).map(|i| NaiveDateTime::from_timestamp(60 * i as i64,0)).collect();
+    /// let ts = TimeSeries::from_vecs(index, values).unwrap();
+    /// fn change_func(prior: &f64, curr: &f64) -> f64{
+    ///     curr - prior
+    /// };
+    /// let ts_difference: TimeSeries<NaiveDateTime,f64> = ts.skip_apply(1, change_func).collect();
+    /// fn perc_change_func(prior: &f64, curr: &f64) -> f64{
+    ///     (curr - prior)/prior
+    /// };
+    /// let ts_percent_change: TimeSeries<NaiveDateTime,f64> = ts.skip_apply(1, perc_change_func).collect();
+    /// 
+    /// ```
+    pub fn skip_apply<TRes,F>(&self, skip_span: usize, transform_func: F) -> SkipApplyTimeSeriesIter<TDate,T, TRes, F>
+    where 
+        TRes : Copy,
+        F : FnMut(&T,&T)->TRes
+    {
+        SkipApplyTimeSeriesIter::new(&self, skip_span, transform_func)
+    }
+    /// inner join two series and apply the desired UDF
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use tsxlib::data_elements::TimeSeriesDataPoint;
+    ///
+    /// let values : Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+    /// let values2 : Vec<f64> = vec![1.0, 2.0, 4.0];
+    /// let index: Vec<i32> = (0..values.len()).map(|i| i as i32).collect();
+    /// let index2: Vec<i32> = (0..values2.len()).map(|i| i as i32).collect();
+    /// let ts = TimeSeries::from_vecs(index, values).unwrap();
+    /// let ts1 = TimeSeries::from_vecs(index2, values2).unwrap();
+    /// let tsres = ts.cross_apply_inner(&ts1,|a,b| (*a,*b));
+    /// let expected = vec![
+    ///     TimeSeriesDataPoint { timestamp: 0, value: (1.00, 1.00) },
+    ///     TimeSeriesDataPoint { timestamp: 1, value: (2.00, 2.00) },
+    ///     TimeSeriesDataPoint { timestamp: 2, value: (3.00, 4.00) },
+    /// ];
+    /// let ts_expected = TimeSeries::from_tsdatapoints(expected).unwrap();
+    /// assert_eq!(ts_expected, tsres)
+    /// ```
+    pub fn cross_apply_inner<T2,T3,F>(&self, other: &TimeSeries<TDate,T2>, apply_func: F) -> TimeSeries<TDate,T3>
+    where 
+        T2 : Clone, 
+        T3 : Clone,
+        F : Fn(&T,&T2) -> T3
+    {
+        self.cross_apply_inner_with_strategy(other, apply_func, JoinStrategy::Merge)
+    }
+
+    /// Inner join two series with the given join strategy and apply the desired UDF
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use tsxlib::joins::JoinStrategy;
+    ///
+    /// let ts = TimeSeries::from_vecs((0..1000).collect(), (0..1000).map(|x| x as f64).collect()).unwrap();
+    /// let ts1 = TimeSeries::from_vecs(vec![10, 500, 2000], vec![1.0, 2.0, 3.0]).unwrap();
+    /// let tsres = ts.cross_apply_inner_with_strategy(&ts1,|a,b| a * b, JoinStrategy::Auto);
+    /// let ts_expected = TimeSeries::from_vecs(vec![10, 500], vec![10.0, 1000.0]).unwrap();
+    /// assert_eq!(ts_expected, tsres)
+    /// ```
+    pub fn cross_apply_inner_with_strategy<T2,T3,F>(&self, other: &TimeSeries<TDate,T2>, apply_func: F, strategy: JoinStrategy) -> TimeSeries<TDate,T3>
+    where 
+        T2 : Clone, 
+        T3 : Clone,
+        F : Fn(&T,&T2) -> T3
+    {
+        let je = JoinEngine{idx_this : &self.timeindicies ,idx_other : &other.timeindicies};
+        let indexes = je.get_inner_joined_indicies(strategy);
+        //can make this parallel if you want...
+        indexes.iter().map(|x| TimeSeriesDataPoint { timestamp : self.timeindicies[x.this_idx].clone(), value : apply_func(&self.values[x.this_idx], &other.values[x.other_idx]) } ).collect()
+    }
+
+    /// Left join two series and apply the desired UDF
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use tsxlib::data_elements::TimeSeriesDataPoint;
+    ///
+    /// let values : Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+    /// let values2 : Vec<f64> = vec![1.0, 2.0, 4.0];
+    /// let index: Vec<i32> = (0..values.len()).map(|i| i as i32).collect();
+    /// let index2: Vec<i32> = (0..values2.len()).map(|i| i as i32).collect();
+    /// let ts = TimeSeries::from_vecs(index, values).unwrap();
+    /// let ts1 = TimeSeries::from_vecs(index2, values2).unwrap();
+    /// let tsres = ts.cross_apply_left(&ts1,|a,b| (*a, match b { Some(v) => Some(*v), _ => None }));
+    /// let expected = vec![
+    ///     TimeSeriesDataPoint { timestamp: 0, value: (1.00, Some(1.00)) },
+    ///     TimeSeriesDataPoint { timestamp: 1, value: (2.00, Some(2.00)) },
+    ///     TimeSeriesDataPoint { timestamp: 2, value: (3.00, Some(4.0)) },
+    ///     TimeSeriesDataPoint { timestamp: 3, value: (4.00, None) },
+    ///     TimeSeriesDataPoint { timestamp: 4, value: (5.00, None) },
+    /// ];
+    /// let ts_expected = TimeSeries::from_tsdatapoints(expected).unwrap();
+    /// assert_eq!(ts_expected, tsres)
+    /// ```
+    pub fn cross_apply_left<T2,T3,F>(&self, other: &TimeSeries<TDate,T2>, apply_func: F) -> TimeSeries<TDate,T3>
+    where 
+        T2 : Clone , 
+        T3 : Clone + fmt::Debug,
+        F : Fn(&T,Option<&T2>) -> T3
+    {
+        self.cross_apply_left_with_strategy(other, apply_func, JoinStrategy::Merge)
+    }
+
+    /// Left join two series with the given join strategy and apply the desired UDF
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use tsxlib::joins::JoinStrategy;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 3], vec![1.0, 2.0, 3.0]).unwrap();
+    /// let ts1 = TimeSeries::from_vecs(vec![2, 3, 4], vec![20.0, 30.0, 40.0]).unwrap();
+    /// let tsres = ts.cross_apply_left_with_strategy(&ts1,|a,b| (*a, b.copied()), JoinStrategy::Hash);
+    /// let ts_expected = TimeSeries::from_vecs(vec![1, 2, 3], vec![(1.0, None), (2.0, Some(20.0)), (3.0, Some(30.0))]).unwrap();
+    /// assert_eq!(ts_expected, tsres)
+    /// ```
+    pub fn cross_apply_left_with_strategy<T2,T3,F>(&self, other: &TimeSeries<TDate,T2>, apply_func: F, strategy: JoinStrategy) -> TimeSeries<TDate,T3>
+    where 
+        T2 : Clone , 
+        T3 : Clone + fmt::Debug,
+        F : Fn(&T,Option<&T2>) -> T3
+    {
+        let je = JoinEngine{idx_this : &self.timeindicies ,idx_other : &other.timeindicies};
+        let indexes = je.get_left_joined_indicies(strategy);
+        //can make this parallel if you want...
+        indexes.iter().map(|x| 
+            TimeSeriesDataPoint { 
+                timestamp : self.timeindicies[x.this_idx].clone(), 
+                value : apply_func(
+                    &self.values[x.this_idx],  
+                    match x.other_idx.is_some() {
+                        true => Some(&other.values[x.other_idx.unwrap()]),
+                        false => None
+                    }
+                )} )
+                .collect()
+    }
+    /// Right join two series and apply the desired UDF
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    /// use tsxlib::data_elements::TimeSeriesDataPoint;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 4], vec![1.0, 2.0, 4.0]).unwrap();
+    /// let ts1 = TimeSeries::from_vecs(vec![2, 3, 4], vec![20.0, 30.0, 40.0]).unwrap();
+    /// let tsres = ts.cross_apply_right(&ts1,|a,b| (a.copied(), *b));
+    /// let expected = vec![
+    ///     TimeSeriesDataPoint { timestamp: 2, value: (Some(2.00), 20.00) },
+    ///     TimeSeriesDataPoint { timestamp: 3, value: (None, 30.00) },
+    ///     TimeSeriesDataPoint { timestamp: 4, value: (Some(4.00), 40.00) },
+    /// ];
+    /// let ts_expected = TimeSeries::from_tsdatapoints(expected).unwrap();
+    /// assert_eq!(ts_expected, tsres)
+    /// ```
+    pub fn cross_apply_right<T2,T3,F>(&self, other: &TimeSeries<TDate,T2>, apply_func: F) -> TimeSeries<TDate,T3>
+    where 
+        T2 : Clone, 
+        T3 : Clone,
+        F : Fn(Option<&T>,&T2) -> T3
+    {
+        let je = JoinEngine{idx_this : &self.timeindicies ,idx_other : &other.timeindicies};
+        let indexes = je.get_right_merge_joined_indicies();
+        //can make this parallel if you want...
+        indexes.iter().map(|x| 
+            TimeSeriesDataPoint { 
+                timestamp : other.timeindicies[x.other_idx].clone(), 
+                value : apply_func(
+                    x.this_idx.map(|idx| &self.values[idx]),  
+                    &other.values[x.other_idx]
+                )} )
+                .collect()
+    }
+
+    /// Semi join, keep the points of this series whose timestamp is also in the other series. Values of the other series are not used
     ///
     /// # Example
     ///
     /// ```
     /// use tsxlib::timeseries::TimeSeries;
-    /// use chrono::NaiveDateTime;
     ///
-    /// let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
-    /// let index = (0..values.len()).map(|i| NaiveDateTime::from_timestamp(60 * i as i64,0)).collect();
-    /// let ts = TimeSeries::from_vecs(index, values).unwrap();
-    /// fn change_func(prior: &f64, curr: &f64) -> f64{
-    ///     curr - prior
-    /// };
-    /// let ts_difference: TimeSeries<NaiveDateTime,f64> = ts.skip_apply(1, change_func).collect();
-    /// fn perc_change_func(prior: &f64, curr: &f64) -> f64{
-    ///     (curr - prior)/prior
-    /// };
-    /// let ts_percent_change: TimeSeries<NaiveDateTime,f64> = ts.skip_apply(1, perc_change_func).collect();
-    /// 
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    /// let ts1 = TimeSeries::from_vecs(vec![2, 4, 5], vec!["a", "b", "c"]).unwrap();
+    /// let ts_expected = TimeSeries::from_vecs(vec![2, 4], vec![2.0, 4.0]).unwrap();
+    /// assert_eq!(ts_expected, ts.semi_join(&ts1))
     /// ```
-    pub fn skip_apply<TRes>(&self, skip_span: usize, transform_func: fn(&T,&T)->TRes) -> SkipApplyTimeSeriesIter<TDate,T, TRes>
-    where TRes : Copy
+    pub fn semi_join<T2: Clone>(&self, other: &TimeSeries<TDate,T2>) -> TimeSeries<TDate,T>
     {
-        SkipApplyTimeSeriesIter::new(&self, skip_span, transform_func)
+        let je = JoinEngine{idx_this : &self.timeindicies ,idx_other : &other.timeindicies};
+        let indexes = je.get_semi_merge_joined_indicies();
+        self.select_positions(&indexes)
     }
-    /// inner join two series and apply the desired UDF
+
+    /// Anti join, keep the points of this series whose timestamp is not in the other series
     ///
     /// # Example
     ///
     /// ```
     /// use tsxlib::timeseries::TimeSeries;
-    /// use tsxlib::data_elements::TimeSeriesDataPoint;
     ///
-    /// let values : Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
-    /// let values2 : Vec<f64> = vec![1.0, 2.0, 4.0];
-    /// let index: Vec<i32> = (0..values.len()).map(|i| i as i32).collect();
-    /// let index2: Vec<i32> = (0..values2.len()).map(|i| i as i32).collect();
-    /// let ts = TimeSeries::from_vecs(index, values).unwrap();
-    /// let ts1 = TimeSeries::from_vecs(index2, values2).unwrap();
-    /// let tsres = ts.cross_apply_inner(&ts1,|a,b| (*a,*b));
-    /// let expected = vec![
-    ///     TimeSeriesDataPoint { timestamp: 0, value: (1.00, 1.00) },
-    ///     TimeSeriesDataPoint { timestamp: 1, value: (2.00, 2.00) },
-    ///     TimeSeriesDataPoint { timestamp: 2, value: (3.00, 4.00) },
-    /// ];
-    /// let ts_expected = TimeSeries::from_tsdatapoints(expected).unwrap();
-    /// assert_eq!(ts_expected, tsres)
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    /// let ts1 = TimeSeries::from_vecs(vec![2, 4, 5], vec!["a", "b", "c"]).unwrap();
+    /// let ts_expected = TimeSeries::from_vecs(vec![1, 3], vec![1.0, 3.0]).unwrap();
+    /// assert_eq!(ts_expected, ts.anti_join(&ts1))
     /// ```
-    pub fn cross_apply_inner<T2,T3>(&self, other: &TimeSeries<TDate,T2>, apply_func: fn(&T,&T2) -> T3) -> TimeSeries<TDate,T3>
-    where 
-        T2 : Clone, 
-        T3 : Clone
+    pub fn anti_join<T2: Clone>(&self, other: &TimeSeries<TDate,T2>) -> TimeSeries<TDate,T>
     {
         let je = JoinEngine{idx_this : &self.timeindicies ,idx_other : &other.timeindicies};
-        let indexes = je.get_inner_merge_joined_indicies();
-        //can make this parallel if you want...
-        indexes.iter().map(|x| TimeSeriesDataPoint { timestamp : self.timeindicies[x.this_idx].clone(), value : apply_func(&self.values[x.this_idx], &other.values[x.other_idx]) } ).collect()
+        let indexes = je.get_anti_merge_joined_indicies();
+        self.select_positions(&indexes)
     }
 
-    /// Left join two series and apply the desired UDF
+    fn select_positions(&self, positions: &[usize]) -> TimeSeries<TDate,T> {
+        let index = positions.iter().map(|pos| self.timeindicies[*pos].clone()).collect();
+        let values = positions.iter().map(|pos| self.values[*pos].clone()).collect();
+        TimeSeries::from_vecs_unchecked(HashableIndex::new(index), values)
+    }
+
+    /// Outer join two series and apply the desired UDF, the result contains the union of both indicies
     ///
     /// # Example
     ///
@@ -500,40 +1480,37 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
     /// use tsxlib::timeseries::TimeSeries;
     /// use tsxlib::data_elements::TimeSeriesDataPoint;
     ///
-    /// let values : Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0];
-    /// let values2 : Vec<f64> = vec![1.0, 2.0, 4.0];
-    /// let index: Vec<i32> = (0..values.len()).map(|i| i as i32).collect();
-    /// let index2: Vec<i32> = (0..values2.len()).map(|i| i as i32).collect();
-    /// let ts = TimeSeries::from_vecs(index, values).unwrap();
-    /// let ts1 = TimeSeries::from_vecs(index2, values2).unwrap();
-    /// let tsres = ts.cross_apply_left(&ts1,|a,b| (*a, match b { Some(v) => Some(*v), _ => None }));
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 4], vec![1.0, 2.0, 4.0]).unwrap();
+    /// let ts1 = TimeSeries::from_vecs(vec![2, 3, 4, 5], vec![20.0, 30.0, 40.0, 50.0]).unwrap();
+    /// let tsres = ts.cross_apply_outer(&ts1,|a,b| (a.copied(), b.copied()));
     /// let expected = vec![
-    ///     TimeSeriesDataPoint { timestamp: 0, value: (1.00, Some(1.00)) },
-    ///     TimeSeriesDataPoint { timestamp: 1, value: (2.00, Some(2.00)) },
-    ///     TimeSeriesDataPoint { timestamp: 2, value: (3.00, Some(4.0)) },
-    ///     TimeSeriesDataPoint { timestamp: 3, value: (4.00, None) },
-    ///     TimeSeriesDataPoint { timestamp: 4, value: (5.00, None) },
+    ///     TimeSeriesDataPoint { timestamp: 1, value: (Some(1.00), None) },
+    ///     TimeSeriesDataPoint { timestamp: 2, value: (Some(2.00), Some(20.00)) },
+    ///     TimeSeriesDataPoint { timestamp: 3, value: (None, Some(30.00)) },
+    ///     TimeSeriesDataPoint { timestamp: 4, value: (Some(4.00), Some(40.00)) },
+    ///     TimeSeriesDataPoint { timestamp: 5, value: (None, Some(50.00)) },
     /// ];
     /// let ts_expected = TimeSeries::from_tsdatapoints(expected).unwrap();
     /// assert_eq!(ts_expected, tsres)
     /// ```
-    pub fn cross_apply_left<T2,T3>(&self, other: &TimeSeries<TDate,T2>, apply_func: fn(&T,Option<&T2>) -> T3) -> TimeSeries<TDate,T3>
+    pub fn cross_apply_outer<T2,T3,F>(&self, other: &TimeSeries<TDate,T2>, apply_func: F) -> TimeSeries<TDate,T3>
     where 
-        T2 : Clone , 
-        T3 : Clone + fmt::Debug
+        T2 : Clone, 
+        T3 : Clone,
+        F : Fn(Option<&T>,Option<&T2>) -> T3
     {
         let je = JoinEngine{idx_this : &self.timeindicies ,idx_other : &other.timeindicies};
-        let indexes = je.get_left_merge_joined_indicies();
+        let indexes = je.get_outer_merge_joined_indicies();
         //can make this parallel if you want...
         indexes.iter().map(|x| 
             TimeSeriesDataPoint { 
-                timestamp : self.timeindicies[x.this_idx].clone(), 
+                timestamp : match x.this_idx {
+                    Some(idx) => self.timeindicies[idx].clone(),
+                    None => other.timeindicies[x.other_idx.unwrap()].clone()
+                }, 
                 value : apply_func(
-                    &self.values[x.this_idx],  
-                    match x.other_idx.is_some() {
-                        true => Some(&other.values[x.other_idx.unwrap()]),
-                        false => None
-                    }
+                    x.this_idx.map(|idx| &self.values[idx]),  
+                    x.other_idx.map(|idx| &other.values[idx])
                 )} )
                 .collect()
     }
@@ -575,27 +1552,36 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
     /// 
     /// assert_eq!(result, ts_expected);
     /// ```
-    pub fn merge_apply_asof<T2,T3>(&self, other: &TimeSeries<TDate,T2>, compare_func: Option<Box<dyn Fn(&TDate,&TDate,&TDate)->(cmp::Ordering,i64)>>, apply_func: fn(&T,Option<&T2>) -> T3,merge_mode :MergeAsofMode) -> TimeSeries<TDate,T3>
+    pub fn merge_apply_asof<T2,T3,F>(&self, other: &TimeSeries<TDate,T2>, compare_func: Option<AsofCompareFunc<TDate>>, apply_func: F,merge_mode :MergeAsofMode) -> TimeSeries<TDate,T3>
     where 
         T2 : Clone, 
-        T3 : Clone
-    { #![allow(clippy::type_complexity)] #![allow(clippy::redundant_closure)]
-        match merge_mode {
-            MergeAsofMode::NoRoll if  compare_func.is_some() => panic!("you cannot have a roll function if you do not set a merge as of mode"),
-            _ => ()
-        };
+        T3 : Clone,
+        F : Fn(&T,Option<&T2>) -> T3
+    {
+        self.merge_apply_asof_allow_exact(other, compare_func, apply_func, merge_mode, true)
+    }
 
-        let je = JoinEngine{idx_this : &self.timeindicies ,idx_other : &other.timeindicies};
-        
-        let other_idx_func:Option<Box<dyn Fn(usize)->usize>> = match merge_mode {
-            MergeAsofMode::RollFollowing => {
-                let otherlen = other.timeindicies.len();
-                Some(Box::new(move |idx: usize| crate::joins::fwd_func(idx, otherlen)))
-            },
-            MergeAsofMode::RollPrior => Some(Box::new(|idx: usize| crate::joins::prior_func(idx))),
-            MergeAsofMode::NoRoll => None
-        };
-        let indexes = je.get_asof_merge_joined_indicies(compare_func,other_idx_func);
+    /// Same as `merge_apply_asof` but if `allow_exact_matches` is false a point of the other series with the same timestamp is never matched, i.e. with `MergeAsofMode::RollPrior` only strictly prior points are matched
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::{TimeSeries,MergeAsofMode};
+    /// use tsxlib::algo::int_utils;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 3], vec![1, 2, 3]).unwrap();
+    /// let ts_join = TimeSeries::from_vecs(vec![2], vec![20]).unwrap();
+    ///
+    /// let result = ts.merge_apply_asof_allow_exact(&ts_join,Some(int_utils::merge_asof_prior(1)),|a,b| (*a, b.copied()), MergeAsofMode::RollPrior, false);
+    /// assert_eq!(result.values(), vec![(1, None), (2, None), (3, Some(20))]);
+    /// ```
+    pub fn merge_apply_asof_allow_exact<T2,T3,F>(&self, other: &TimeSeries<TDate,T2>, compare_func: Option<AsofCompareFunc<TDate>>, apply_func: F,merge_mode :MergeAsofMode, allow_exact_matches: bool) -> TimeSeries<TDate,T3>
+    where 
+        T2 : Clone, 
+        T3 : Clone,
+        F : Fn(&T,Option<&T2>) -> T3
+    {
+        let indexes = self.get_asof_indicies(other, compare_func, merge_mode, allow_exact_matches);
         //can make this parallel if you want...
         indexes.iter().map(|x| 
             TimeSeriesDataPoint { 
@@ -610,6 +1596,74 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
         .collect()
     }
 
+    /// Same as `merge_apply_asof` but the UDF also gets the timestamp of this point and the matched point of the other series, i.e. so you can see how stale a match is
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::{TimeSeries,MergeAsofMode};
+    /// use tsxlib::algo::time_distance;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![10i64, 20, 30, 40], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    /// let ts_join = TimeSeries::from_vecs(vec![9i64, 25], vec![100.0, 200.0]).unwrap();
+    /// 
+    /// // flag matches that are more than 10 units old
+    /// let result = ts.merge_apply_asof_with_match(&ts_join,Some(time_distance::merge_asof_prior(100)),|stamp,a,matched| match matched {
+    ///     Some(dp) => (*a, Some(*dp.value), stamp - dp.timestamp > 10),
+    ///     None => (*a, None, false)
+    /// }, MergeAsofMode::RollPrior);
+    /// 
+    /// let expected = vec![(1.0, Some(100.0), false), (2.0, Some(100.0), true), (3.0, Some(200.0), false), (4.0, Some(200.0), true)];
+    /// assert_eq!(result.values(), expected);
+    /// ```
+    pub fn merge_apply_asof_with_match<T2,T3,F>(&self, other: &TimeSeries<TDate,T2>, compare_func: Option<AsofCompareFunc<TDate>>, apply_func: F,merge_mode :MergeAsofMode) -> TimeSeries<TDate,T3>
+    where 
+        T2 : Clone, 
+        T3 : Clone,
+        F : Fn(&TDate,&T,Option<TimeSeriesDataPoint<&TDate,&T2>>) -> T3
+    {
+        self.merge_apply_asof_with_match_allow_exact(other, compare_func, apply_func, merge_mode, true)
+    }
+
+    /// Same as `merge_apply_asof_with_match` but exact matches can be excluded like in `merge_apply_asof_allow_exact`
+    pub fn merge_apply_asof_with_match_allow_exact<T2,T3,F>(&self, other: &TimeSeries<TDate,T2>, compare_func: Option<AsofCompareFunc<TDate>>, apply_func: F,merge_mode :MergeAsofMode, allow_exact_matches: bool) -> TimeSeries<TDate,T3>
+    where 
+        T2 : Clone, 
+        T3 : Clone,
+        F : Fn(&TDate,&T,Option<TimeSeriesDataPoint<&TDate,&T2>>) -> T3
+    {
+        let indexes = self.get_asof_indicies(other, compare_func, merge_mode, allow_exact_matches);
+        //can make this parallel if you want...
+        indexes.iter().map(|x| 
+            TimeSeriesDataPoint { 
+                timestamp : self.timeindicies[x.this_idx].clone(), 
+                value : apply_func(
+                    &self.timeindicies[x.this_idx],
+                    &self.values[x.this_idx],  
+                    x.other_idx.map(|idx| TimeSeriesDataPoint::new(&other.timeindicies[idx], &other.values[idx]))
+                )} )
+        .collect()
+    }
+
+    fn get_asof_indicies<T2: Clone>(&self, other: &TimeSeries<TDate,T2>, compare_func: Option<AsofCompareFunc<TDate>>, merge_mode :MergeAsofMode, allow_exact_matches: bool) -> Vec<IndexJoinPotentiallyUnmatchedPair>
+    { #![allow(clippy::redundant_closure)]
+        match merge_mode {
+            MergeAsofMode::NoRoll if  compare_func.is_some() => panic!("you cannot have a roll function if you do not set a merge as of mode"),
+            _ => ()
+        };
+
+        let je = JoinEngine{idx_this : &self.timeindicies ,idx_other : &other.timeindicies};
+        
+        let other_idx_func:Option<Box<dyn Fn(usize)->usize>> = match merge_mode {
+            MergeAsofMode::RollFollowing => {
+                let otherlen = other.timeindicies.len();
+                Some(Box::new(move |idx: usize| crate::joins::fwd_func(idx, otherlen)))
+            },
+            MergeAsofMode::RollPrior | MergeAsofMode::Nearest => Some(Box::new(|idx: usize| crate::joins::prior_func(idx))),
+            MergeAsofMode::NoRoll => None
+        };
+        je.get_asof_merge_joined_indicies_allow_exact(compare_func,other_idx_func,allow_exact_matches)
+    }
 
     /// Interweave series. If a set of points happens to match then the selec_func is used to pick (or generate one)
     ///
@@ -648,7 +1702,9 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
     /// let ts_merged = ts1.interweave(&ts2,|left,_right| left);
     /// assert_eq!(ts_merged, ts_expected);
     /// ```    
-    pub fn interweave(&self, other: &TimeSeries<TDate,T>, selec_func: fn(TimeSeriesDataPoint<TDate,T>,TimeSeriesDataPoint<TDate,T>)->TimeSeriesDataPoint<TDate,T>  ) -> TimeSeries<TDate,T> {  #![allow(clippy::type_complexity)]
+    pub fn interweave<F>(&self, other: &TimeSeries<TDate,T>, selec_func: F) -> TimeSeries<TDate,T> 
+    where F : Fn(TimeSeriesDataPoint<TDate,T>,TimeSeriesDataPoint<TDate,T>)->TimeSeriesDataPoint<TDate,T>
+    {
         let mut output: Vec<TimeSeriesDataPoint<TDate,T>> = Vec::new();
         let mut pos1 = 0;
         let mut pos2 = 0;
@@ -687,6 +1743,101 @@ impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<
 }
 
 
+/// fills missing values with the last value, at most `limit` in a row
+fn fill_forward<'a, T: Clone + 'a, I: Iterator<Item = &'a Option<T>>>(values: I, limit: Option<usize>) -> Vec<Option<T>> {
+    let mut last: Option<&T> = None;
+    let mut missing = 0;
+    values.map(|value| match value {
+        Some(v) => {
+            last = Some(v);
+            missing = 0;
+            Some(v.clone())
+        },
+        None => {
+            missing += 1;
+            match limit {
+                Some(lim) if missing > lim => None,
+                _ => last.cloned()
+            }
+        }
+    }).collect()
+}
+
+/// Missing value handling for series of optional values, i.e. the output of `cross_apply_left` or `reindex`
+impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> TimeSeries<TDate, Option<T>> {
+    /// Fill missing values with the last value, at most `limit` in a row
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::TimeSeries;
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4, 5], vec![None, Some(1.0), None, None, Some(2.0)]).unwrap();
+    /// assert_eq!(ts.ffill(None).values(), vec![None, Some(1.0), Some(1.0), Some(1.0), Some(2.0)]);
+    /// assert_eq!(ts.ffill(Some(1)).values(), vec![None, Some(1.0), Some(1.0), None, Some(2.0)]);
+    /// ```
+    pub fn ffill(&self, limit: Option<usize>) -> TimeSeries<TDate,Option<T>> {
+        TimeSeries::from_vecs_unchecked(self.timeindicies.clone(), fill_forward(self.values.iter(), limit))
+    }
+
+    /// Fill missing values with the next value, at most `limit` in a row
+    pub fn bfill(&self, limit: Option<usize>) -> TimeSeries<TDate,Option<T>> {
+        let mut values = fill_forward(self.values.iter().rev(), limit);
+        values.reverse();
+        TimeSeries::from_vecs_unchecked(self.timeindicies.clone(), values)
+    }
+
+    /// Replace all missing values with `value`
+    pub fn fillna(&self, value: T) -> TimeSeries<TDate,T> {
+        let values = self.values.iter().map(|v| v.clone().unwrap_or_else(|| value.clone())).collect();
+        TimeSeries::from_vecs_unchecked(self.timeindicies.clone(), values)
+    }
+
+    /// Drop the points with missing values
+    pub fn dropna(&self) -> TimeSeries<TDate,T> {
+        self.iter().filter_map(|dp| dp.value.clone().map(|v| TimeSeriesDataPoint::new(dp.timestamp.clone(), v))).collect_from_unchecked_iter()
+    }
+
+    /// Fill missing values from the values around them. `Linear`, `Time` and `Nearest` only fill the gaps between two values while `Previous` also fills the end of the series
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tsxlib::timeseries::{TimeSeries,InterpolationMode};
+    ///
+    /// let ts = TimeSeries::from_vecs(vec![0, 1, 4, 5], vec![Some(0.0), None, None, Some(9.0)]).unwrap();
+    /// assert_eq!(ts.interpolate(InterpolationMode::Linear).values(), vec![Some(0.0), Some(3.0), Some(6.0), Some(9.0)]);
+    /// assert_eq!(ts.interpolate(InterpolationMode::Time).values(), vec![Some(0.0), Some(1.8), Some(7.2), Some(9.0)]);
+    /// ```
+    pub fn interpolate(&self, mode: InterpolationMode) -> TimeSeries<TDate,Option<T>>
+    where
+        TDate: TimeStep,
+        T: Interpolate
+    {
+        if mode == InterpolationMode::Previous {
+            return self.ffill(None);
+        }
+        let mut values = self.values.clone();
+        let known: Vec<usize> = self.values.iter().enumerate().filter(|(_, v)| v.is_some()).map(|(pos, _)| pos).collect();
+        for (&prior, &next) in known.iter().zip(known.iter().skip(1)) {
+            let (start, end) = (&self.timeindicies[prior], &self.timeindicies[next]);
+            let (prior_value, next_value) = (self.values[prior].as_ref().unwrap(), self.values[next].as_ref().unwrap());
+            for (pos, value) in values.iter_mut().enumerate().take(next).skip(prior + 1) {
+                let key = &self.timeindicies[pos];
+                *value = Some(match mode {
+                    InterpolationMode::Linear => prior_value.interpolate(next_value, (pos - prior) as f64 / (next - prior) as f64),
+                    InterpolationMode::Time => prior_value.interpolate(next_value, TDate::delta_ratio(&key.delta(start), &end.delta(start))),
+                    _ => match end.delta(key) < key.delta(start) {
+                        true => next_value.clone(),
+                        false => prior_value.clone()
+                    }
+                });
+            }
+        }
+        TimeSeries::from_vecs_unchecked(self.timeindicies.clone(), values)
+    }
+}
+
 impl<TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> FromIterator<TimeSeriesDataPoint<TDate,T>> for TimeSeries<TDate,T> {
     fn from_iter<Tin>(iter: Tin) -> Self
     where
@@ -759,9 +1910,15 @@ mod tests {
         let index = vec![1, 2, 3, 4, 5];
         let ts = TimeSeries::from_vecs(index.iter().map(|x| NaiveDateTime::from_timestamp((x.clone()) as i64,0)).collect(), values);
 
-        let result = ts.map_err(|e| e.kind());
-        let expected = Err(std::io::ErrorKind::InvalidData);
-        assert_eq!(result, expected);
+        assert!(matches!(ts, Err(TsxError::LengthMismatch{ index: 5, values: 3 })));
+    }
+
+    #[test]
+    fn test_new_invalid_index() {
+        let dupes = TimeSeries::from_vecs(vec![1, 2, 2, 3], vec![1.0, 2.0, 3.0, 4.0]);
+        let unsorted = TimeSeries::from_vecs(vec![1, 2, 4, 3], vec![1.0, 2.0, 3.0, 4.0]);
+        assert!(matches!(dupes, Err(TsxError::DuplicateTimestamp{ position: 2 })));
+        assert!(matches!(unsorted, Err(TsxError::NonMonotonicIndex{ position: 3 })));
     }
 
     #[test]
@@ -1230,4 +2387,496 @@ mod tests {
         assert_eq!(ts_expected, tsres)
     }
 
+    #[test]
+    fn test_outer_join(){
+        // disjoint
+        let ts = TimeSeries::from_vecs(vec![1, 3, 5], vec![1.0, 3.0, 5.0]).unwrap();
+        let ts1 = TimeSeries::from_vecs(vec![2, 4], vec![2.0, 4.0]).unwrap();
+        let tsres = ts.cross_apply_outer(&ts1,|a,b| (a.copied(), b.copied()));
+        let expected = vec![
+            TimeSeriesDataPoint { timestamp: 1, value: (Some(1.0), None) },
+            TimeSeriesDataPoint { timestamp: 2, value: (None, Some(2.0)) },
+            TimeSeriesDataPoint { timestamp: 3, value: (Some(3.0), None) },
+            TimeSeriesDataPoint { timestamp: 4, value: (None, Some(4.0)) },
+            TimeSeriesDataPoint { timestamp: 5, value: (Some(5.0), None) },
+        ];
+        assert_eq!(TimeSeries::from_tsdatapoints(expected).unwrap(), tsres);
+
+        // overlapping
+        let ts1 = TimeSeries::from_vecs(vec![3, 4, 5, 6], vec![30.0, 40.0, 50.0, 60.0]).unwrap();
+        let tsres = ts.cross_apply_outer(&ts1,|a,b| (a.copied(), b.copied()));
+        let expected = vec![
+            TimeSeriesDataPoint { timestamp: 1, value: (Some(1.0), None) },
+            TimeSeriesDataPoint { timestamp: 3, value: (Some(3.0), Some(30.0)) },
+            TimeSeriesDataPoint { timestamp: 4, value: (None, Some(40.0)) },
+            TimeSeriesDataPoint { timestamp: 5, value: (Some(5.0), Some(50.0)) },
+            TimeSeriesDataPoint { timestamp: 6, value: (None, Some(60.0)) },
+        ];
+        assert_eq!(TimeSeries::from_tsdatapoints(expected).unwrap(), tsres);
+
+        // identical
+        let tsres = ts.cross_apply_outer(&ts,|a,b| a.unwrap() + b.unwrap());
+        assert_eq!(TimeSeries::from_vecs(vec![1, 3, 5], vec![2.0, 6.0, 10.0]).unwrap(), tsres);
+
+        // empty
+        let empty: TimeSeries<i32,f64> = TimeSeries::empty();
+        let tsres = empty.cross_apply_outer(&ts,|a,b| (a.copied(), b.copied()));
+        assert_eq!(ts.map(|x| (None, Some(*x))), tsres);
+    }
+
+    #[test]
+    fn test_right_anti_semi_join(){
+        let ts = TimeSeries::from_vecs(vec![1, 2, 4, 6], vec![1.0, 2.0, 4.0, 6.0]).unwrap();
+        let ts1 = TimeSeries::from_vecs(vec![2, 3, 4, 7], vec![20, 30, 40, 70]).unwrap();
+
+        let tsres = ts.cross_apply_right(&ts1,|a,b| (a.copied(), *b));
+        let expected = vec![
+            TimeSeriesDataPoint { timestamp: 2, value: (Some(2.0), 20) },
+            TimeSeriesDataPoint { timestamp: 3, value: (None, 30) },
+            TimeSeriesDataPoint { timestamp: 4, value: (Some(4.0), 40) },
+            TimeSeriesDataPoint { timestamp: 7, value: (None, 70) },
+        ];
+        assert_eq!(TimeSeries::from_tsdatapoints(expected).unwrap(), tsres);
+
+        assert_eq!(TimeSeries::from_vecs(vec![2, 4], vec![2.0, 4.0]).unwrap(), ts.semi_join(&ts1));
+        assert_eq!(TimeSeries::from_vecs(vec![1, 6], vec![1.0, 6.0]).unwrap(), ts.anti_join(&ts1));
+
+        let empty: TimeSeries<i32,i32> = TimeSeries::empty();
+        assert_eq!(ts, ts.anti_join(&empty));
+        assert!(ts.semi_join(&empty).is_empty());
+    }
+
+    #[test]
+    fn test_join_with_strategy(){
+        let ts = TimeSeries::from_vecs((0..100).collect(), (0..100).map(|x| x as f64).collect()).unwrap();
+        let ts1 = TimeSeries::from_vecs(vec![-1, 5, 50, 99, 150], vec![1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+        for strategy in [JoinStrategy::Merge, JoinStrategy::Hash, JoinStrategy::Auto].iter() {
+            let inner = ts.cross_apply_inner_with_strategy(&ts1, |a,b| a * b, *strategy);
+            assert_eq!(TimeSeries::from_vecs(vec![5, 50, 99], vec![10.0, 150.0, 396.0]).unwrap(), inner);
+            let inner_rev = ts1.cross_apply_inner_with_strategy(&ts, |a,b| a * b, *strategy);
+            assert_eq!(inner, inner_rev);
+            let left = ts1.cross_apply_left_with_strategy(&ts, |a,b| (*a, b.copied()), *strategy);
+            assert_eq!(ts1.cross_apply_left(&ts, |a,b| (*a, b.copied())), left);
+            assert_eq!(left.values.iter().filter(|x| x.1.is_some()).count(), 3);
+        }
+    }
+
+    #[test]
+    fn test_merge_asof_generic_keys(){
+        use crate::algo::time_distance;
+        use chrono::{DateTime,TimeZone,Utc};
+
+        // epoch nanos, the other index is not a subset of this one
+        let ts = TimeSeries::from_vecs(vec![100i64, 200, 300, 400, 500], vec![1, 2, 3, 4, 5]).unwrap();
+        let ts_join = TimeSeries::from_vecs(vec![50i64, 190, 300, 310, 600], vec![10, 20, 30, 40, 50]).unwrap();
+        let prior = ts.merge_apply_asof(&ts_join, Some(time_distance::merge_asof_prior(60)), |a,b| (*a, b.copied()), MergeAsofMode::RollPrior);
+        assert_eq!(prior.values, vec![(1, Some(10)), (2, Some(20)), (3, Some(30)), (4, None), (5, None)]);
+        let fwd = ts.merge_apply_asof(&ts_join, Some(time_distance::merge_asof_fwd(100)), |a,b| (*a, b.copied()), MergeAsofMode::RollFollowing);
+        assert_eq!(fwd.values, vec![(1, Some(20)), (2, Some(30)), (3, Some(30)), (4, None), (5, Some(50))]);
+        let noroll = ts.merge_apply_asof(&ts_join, None, |a,b| (*a, b.copied()), MergeAsofMode::NoRoll);
+        assert_eq!(noroll.values, vec![(1, None), (2, None), (3, Some(30)), (4, None), (5, None)]);
+
+        // unsigned keys
+        let ts = TimeSeries::from_vecs(vec![1u64, 5, 9], vec![1, 2, 3]).unwrap();
+        let ts_join = TimeSeries::from_vecs(vec![0u64, 4, 8], vec![10, 20, 30]).unwrap();
+        let prior = ts.merge_apply_asof(&ts_join, Some(time_distance::merge_asof_prior(1)), |a,b| (*a, b.copied()), MergeAsofMode::RollPrior);
+        assert_eq!(prior.values, vec![(1, Some(10)), (2, Some(20)), (3, Some(30))]);
+
+        // timezone aware timestamps
+        let stamps: Vec<DateTime<Utc>> = [10, 20, 30].iter().map(|x| Utc.timestamp_opt(*x, 0).unwrap()).collect();
+        let join_stamps: Vec<DateTime<Utc>> = [9, 25].iter().map(|x| Utc.timestamp_opt(*x, 0).unwrap()).collect();
+        let ts = TimeSeries::from_vecs(stamps, vec![1, 2, 3]).unwrap();
+        let ts_join = TimeSeries::from_vecs(join_stamps, vec![10, 20]).unwrap();
+        let prior = ts.merge_apply_asof(&ts_join, Some(time_distance::merge_asof_prior(Duration::seconds(5))), |a,b| (*a, b.copied()), MergeAsofMode::RollPrior);
+        assert_eq!(prior.values, vec![(1, Some(10)), (2, None), (3, Some(20))]);
+    }
+
+    #[test]
+    fn test_naivedatetime_merge_asof_nearest(){
+        use crate::algo::time_distance::NearestTieBreak;
+        let ts = TimeSeries::from_vecs(HashableIndex::from_int_stamps(vec![1000, 2000, 3000, 4000, 5000, 9000]).into_values(), vec![1, 2, 3, 4, 5, 9]).unwrap();
+        let ts_join = TimeSeries::from_vecs(HashableIndex::from_int_stamps(vec![1500, 2500, 3000, 4800]).into_values(), vec![15, 25, 30, 48]).unwrap();
+
+        let nearest = ts.merge_apply_asof(&ts_join, Some(chrono_utils::merge_asof_nearest(None, NearestTieBreak::Prior)), |a,b| (*a, b.copied()), MergeAsofMode::Nearest);
+        assert_eq!(nearest.values, vec![(1, Some(15)), (2, Some(15)), (3, Some(30)), (4, Some(48)), (5, Some(48)), (9, Some(48))]);
+
+        let nearest = ts.merge_apply_asof(&ts_join, Some(chrono_utils::merge_asof_nearest(None, NearestTieBreak::Following)), |a,b| (*a, b.copied()), MergeAsofMode::Nearest);
+        assert_eq!(nearest.values, vec![(1, Some(15)), (2, Some(25)), (3, Some(30)), (4, Some(48)), (5, Some(48)), (9, Some(48))]);
+
+        let nearest = ts.merge_apply_asof(&ts_join, Some(chrono_utils::merge_asof_nearest(Some(Duration::seconds(500)), NearestTieBreak::Prior)), |a,b| (*a, b.copied()), MergeAsofMode::Nearest);
+        assert_eq!(nearest.values, vec![(1, Some(15)), (2, Some(15)), (3, Some(30)), (4, None), (5, Some(48)), (9, None)]);
+
+        let nearest = ts_join.merge_apply_asof(&ts, Some(chrono_utils::merge_asof_nearest(None, NearestTieBreak::Prior)), |a,b| (*a, b.copied()), MergeAsofMode::Nearest);
+        assert_eq!(nearest.values, vec![(15, Some(1)), (25, Some(2)), (30, Some(3)), (48, Some(5))]);
+    }
+
+    #[test]
+    fn test_merge_asof_with_match(){
+        let ts = TimeSeries::from_vecs(HashableIndex::from_int_stamps(vec![1, 2, 3, 5, 8]).into_values(), vec![1, 2, 3, 5, 8]).unwrap();
+        let ts_join = TimeSeries::from_vecs(HashableIndex::from_int_stamps(vec![2, 4, 7]).into_values(), vec![20, 40, 70]).unwrap();
+
+        let lags = ts.merge_apply_asof_with_match(&ts_join, Some(chrono_utils::merge_asof_prior(Duration::seconds(2))), |stamp,_a,matched| matched.map(|dp| (*dp.value, (*stamp - *dp.timestamp).num_seconds())), MergeAsofMode::RollPrior);
+        assert_eq!(lags.values, vec![None, Some((20, 0)), Some((20, 1)), Some((40, 1)), Some((70, 1))]);
+
+        let lags = ts.merge_apply_asof_with_match(&ts_join, Some(chrono_utils::merge_asof_fwd(Duration::seconds(1))), |stamp,_a,matched| matched.map(|dp| (*dp.timestamp - *stamp).num_seconds()), MergeAsofMode::RollFollowing);
+        assert_eq!(lags.values, vec![Some(1), Some(0), Some(1), None, None]);
+
+        // the values match what merge_apply_asof gives
+        let plain = ts.merge_apply_asof(&ts_join, Some(chrono_utils::merge_asof_prior(Duration::seconds(2))), |a,b| (*a, b.copied()), MergeAsofMode::RollPrior);
+        let with_match = ts.merge_apply_asof_with_match(&ts_join, Some(chrono_utils::merge_asof_prior(Duration::seconds(2))), |_stamp,a,b| (*a, b.map(|dp| *dp.value)), MergeAsofMode::RollPrior);
+        assert_eq!(plain, with_match);
+    }
+
+    #[test]
+    fn test_merge_asof_disallow_exact_matches(){
+        let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4, 5, 6], vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let ts_join = TimeSeries::from_vecs(vec![2, 4, 5], vec![20, 40, 50]).unwrap();
+
+        // looking back
+        let prior = ts.merge_apply_asof(&ts_join, Some(int_utils::merge_asof_prior(1)), |a,b| (*a, b.copied()), MergeAsofMode::RollPrior);
+        assert_eq!(prior.values, vec![(1, None), (2, Some(20)), (3, Some(20)), (4, Some(40)), (5, Some(50)), (6, Some(50))]);
+        let prior = ts.merge_apply_asof_allow_exact(&ts_join, Some(int_utils::merge_asof_prior(1)), |a,b| (*a, b.copied()), MergeAsofMode::RollPrior, false);
+        assert_eq!(prior.values, vec![(1, None), (2, None), (3, Some(20)), (4, None), (5, Some(40)), (6, Some(50))]);
+
+        // looking forward
+        let fwd = ts.merge_apply_asof(&ts_join, Some(int_utils::merge_asof_fwd(1)), |a,b| (*a, b.copied()), MergeAsofMode::RollFollowing);
+        assert_eq!(fwd.values, vec![(1, Some(20)), (2, Some(20)), (3, Some(40)), (4, Some(40)), (5, Some(50)), (6, None)]);
+        let fwd = ts.merge_apply_asof_allow_exact(&ts_join, Some(int_utils::merge_asof_fwd(1)), |a,b| (*a, b.copied()), MergeAsofMode::RollFollowing, false);
+        assert_eq!(fwd.values, vec![(1, Some(20)), (2, None), (3, Some(40)), (4, Some(50)), (5, None), (6, None)]);
+
+        // without a roll nothing can match
+        let noroll = ts.merge_apply_asof_allow_exact(&ts_join, None, |a,b| (*a, b.copied()), MergeAsofMode::NoRoll, false);
+        assert!(noroll.values.iter().all(|x| x.1.is_none()));
+
+        // the same index only ever matches the prior point
+        let prior = ts.merge_apply_asof_allow_exact(&ts, Some(int_utils::merge_asof_prior(1)), |a,b| (*a, b.copied()), MergeAsofMode::RollPrior, false);
+        assert_eq!(prior.values, vec![(1, None), (2, Some(1)), (3, Some(2)), (4, Some(3)), (5, Some(4)), (6, Some(5))]);
+        let single = TimeSeries::from_vecs(vec![4], vec![40]).unwrap();
+        let prior = ts.merge_apply_asof_allow_exact(&single, Some(int_utils::merge_asof_prior(10)), |a,b| (*a, b.copied()), MergeAsofMode::RollPrior, false);
+        assert_eq!(prior.values, vec![(1, None), (2, None), (3, None), (4, None), (5, Some(40)), (6, Some(40))]);
+        let lags = ts.merge_apply_asof_with_match_allow_exact(&ts_join, Some(int_utils::merge_asof_prior(1)), |stamp,_a,matched| matched.map(|dp| stamp - dp.timestamp), MergeAsofMode::RollPrior, false);
+        assert_eq!(lags.values, vec![None, None, Some(1), None, Some(1), Some(1)]);
+    }
+
+    #[test]
+    fn test_capturing_closures(){
+        let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+        let threshold = 2.5;
+        let coefficient = 10.0;
+        let lookup: std::collections::HashMap<i32, &str> = vec![(1, "a"), (3, "c")].into_iter().collect();
+
+        assert_eq!(ts.map(|x| *x > threshold).values, vec![false, false, true, true]);
+        assert_eq!(ts.map_with_date(|dt,_x| lookup.get(dt).copied().unwrap_or_default()).values, vec!["a", "", "c", ""]);
+        assert_eq!(ts.cross_apply_inner(&ts, |a,b| a * b * coefficient).values, vec![10.0, 40.0, 90.0, 160.0]);
+        assert_eq!(ts.cross_apply_left(&ts, |a,b| a + b.unwrap() * coefficient).values, vec![11.0, 22.0, 33.0, 44.0]);
+
+        let mut calls = 0;
+        let diffs: TimeSeries<i32,f64> = ts.skip_apply(1, |prior,curr| { calls += 1; (curr - prior) * coefficient }).collect();
+        assert_eq!(diffs.values, vec![10.0, 10.0, 10.0]);
+        assert_eq!(calls, 3);
+
+        let resampled = ts.resample_and_agg(2, |dt,size| (dt / size) * size, |grp| grp.len());
+        assert_eq!(resampled.values, vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn test_cumulative(){
+        let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4, 5], vec![2, -1, 3, 5, -4]).unwrap();
+        assert_eq!(ts.cumsum().values, vec![2, 1, 4, 9, 5]);
+        assert_eq!(ts.cumprod().values, vec![2, -2, -6, -30, 120]);
+        assert_eq!(ts.cummax().values, vec![2, 2, 3, 5, 5]);
+        assert_eq!(ts.cummin().values, vec![2, -1, -1, -1, -4]);
+        assert_eq!(ts.cumsum().timeindicies, ts.timeindicies);
+
+        let floats = TimeSeries::from_vecs(vec![1, 2, 3], vec![1.5, 0.5, 2.0]).unwrap();
+        assert_eq!(floats.cumsum().values, vec![1.5, 2.0, 4.0]);
+        assert_eq!(floats.cumprod().values, vec![1.5, 0.75, 1.5]);
+
+        let empty: TimeSeries<i32,f64> = TimeSeries::empty();
+        assert!(empty.cumsum().is_empty());
+    }
+
+    #[test]
+    fn test_ewm_cov(){
+        let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4], vec![1.0, 3.0, 2.0, 5.0]).unwrap();
+        let ts2 = TimeSeries::from_vecs(vec![1, 2, 4, 5], vec![2.0, 1.0, 4.0, 0.0]).unwrap();
+        let variances: Vec<f64> = ts.ewm(EwmDecay::span(3.0).unwrap(), true).map(|dp| dp.value.var).collect();
+        let own_cov = ts.ewm_cov(&ts, EwmDecay::span(3.0).unwrap(), true, false);
+        assert!(own_cov.values[0].is_nan());
+        assert_eq!(own_cov.values[1..], variances[1..]);
+
+        let cov = ts.ewm_cov(&ts2, EwmDecay::alpha(0.5).unwrap(), true, true);
+        assert_eq!(cov.timeindicies.values(), vec![1, 2, 4]);
+        // common points are (1, 2), (3, 1), (5, 4) with weights 0.25, 0.5, 1
+        let (mx, my) = ((0.25 * 1.0 + 0.5 * 3.0 + 5.0) / 1.75, (0.25 * 2.0 + 0.5 * 1.0 + 4.0) / 1.75);
+        let expected = (0.25 * (1.0 - mx) * (2.0 - my) + 0.5 * (3.0 - mx) * (1.0 - my) + (5.0 - mx) * (4.0 - my)) / 1.75;
+        assert!((cov.values[2] - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_resample_grid(){
+        let ts = TimeSeries::from_vecs(vec![0, 3, 5, 21], vec![1, 2, 3, 4]).unwrap();
+        let sum = |x: &[TimeSeriesDataPoint<&i32,&i32>]| x.iter().map(|dp| *dp.value).sum::<i32>();
+
+        let skipped = ts.resample(5, ResampleOptions::default(), sum).unwrap();
+        assert_eq!(skipped.timeindicies.values(), vec![0, 5, 20]);
+        assert_eq!(skipped.values, vec![Some(3), Some(3), Some(4)]);
+
+        let nulls = ts.resample(5, ResampleOptions { fill_empty: FillEmpty::Null, ..Default::default() }, sum).unwrap();
+        assert_eq!(nulls.timeindicies.values(), vec![0, 5, 10, 15, 20]);
+        assert_eq!(nulls.values, vec![Some(3), Some(3), None, None, Some(4)]);
+
+        let filled = ts.resample(5, ResampleOptions { fill_empty: FillEmpty::ForwardFill, ..Default::default() }, sum).unwrap();
+        assert_eq!(filled.values, vec![Some(3), Some(3), Some(3), Some(3), Some(4)]);
+
+        // with right closed bins the first point closes the bin (-5, 0] and 5 joins (0, 5]
+        let right = ts.resample(5, ResampleOptions { label: BinEdge::Right, closed: BinEdge::Right, fill_empty: FillEmpty::Null, ..Default::default() }, sum).unwrap();
+        assert_eq!(right.timeindicies.values(), vec![0, 5, 10, 15, 20, 25]);
+        assert_eq!(right.values, vec![Some(1), Some(5), None, None, None, Some(4)]);
+
+        let anchored = ts.resample(5, ResampleOptions { origin: ResampleOrigin::At(-2), ..Default::default() }, sum).unwrap();
+        assert_eq!(anchored.timeindicies.values(), vec![-2, 3, 18]);
+        assert_eq!(anchored.values, vec![Some(1), Some(5), Some(4)]);
+
+        let offset = ts.resample(5, ResampleOptions { offset: 1, ..Default::default() }, sum).unwrap();
+        assert_eq!(offset.timeindicies.values(), vec![-4, 1, 21]);
+        assert_eq!(offset.values, vec![Some(1), Some(5), Some(4)]);
+
+        let empty: TimeSeries<i32,i32> = TimeSeries::empty();
+        assert!(empty.resample(5, ResampleOptions::default(), sum).unwrap().is_empty());
+        assert!(matches!(ts.resample(0, ResampleOptions::default(), sum), Err(TsxError::InvalidParameter{ name: "sample size", .. })));
+        assert!(ts.resample(-5, ResampleOptions::default(), sum).is_err());
+
+        // labels past the top of the key type saturate instead of wrapping around
+        let bounded = TimeSeries::from_vecs(vec![250u8, 252, 255], vec![1, 2, 3]).unwrap();
+        let sum_u8 = |x: &[TimeSeriesDataPoint<&u8,&i32>]| x.iter().map(|dp| *dp.value).sum::<i32>();
+        let right = bounded.resample(4, ResampleOptions { label: BinEdge::Right, ..Default::default() }, sum_u8).unwrap();
+        assert_eq!(right.timeindicies.values(), vec![254, 255]);
+        assert_eq!(right.values, vec![Some(3), Some(3)]);
+        let bottom = TimeSeries::from_vecs(vec![-128i8, -127], vec![1, 2]).unwrap();
+        let left = bottom.resample(5, ResampleOptions { offset: -3, ..Default::default() }, |x| x.len()).unwrap();
+        assert_eq!(left.timeindicies.values(), vec![-128]);
+        assert_eq!(left.values, vec![Some(2)]);
+    }
+
+    #[test]
+    fn test_resample_grid_chrono(){
+        let index = HashableIndex::from_int_stamps(vec![0, 30, 61, 90, 300]).into_values();
+        let ts = TimeSeries::from_vecs(index, vec![1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+        let options = ResampleOptions { fill_empty: FillEmpty::Null, ..Default::default() };
+        let counts = ts.resample(Duration::minutes(1), options, |x| x.len()).unwrap();
+        assert_eq!(counts.timeindicies.values(), HashableIndex::from_int_stamps(vec![0, 60, 120, 180, 240, 300]).into_values());
+        assert_eq!(counts.values, vec![Some(2), Some(2), None, None, None, Some(1)]);
+
+        let options = ResampleOptions { label: BinEdge::Right, origin: ResampleOrigin::At(HashableIndex::from_int_stamps(vec![15]).into_values()[0]), ..Default::default() };
+        let last = ts.resample(Duration::minutes(1), options, |x| *x.last().unwrap().value).unwrap();
+        assert_eq!(last.timeindicies.values(), HashableIndex::from_int_stamps(vec![15, 75, 135, 315]).into_values());
+        assert_eq!(last.values, vec![Some(1.0), Some(3.0), Some(4.0), Some(5.0)]);
+    }
+
+
+    #[test]
+    fn test_reindex(){
+        let ts = TimeSeries::from_vecs(vec![2, 4, 8], vec![10, 20, 40]).unwrap();
+        let index = HashableIndex::new(vec![0, 1, 2, 3, 5, 6, 7, 8, 12]);
+
+        assert_eq!(ts.reindex(&index, FillPolicy::None).values, vec![None, None, Some(10), None, None, None, None, Some(40), None]);
+        assert_eq!(ts.reindex(&index, FillPolicy::ForwardFill{ limit: None }).values, vec![None, None, Some(10), Some(10), Some(20), Some(20), Some(20), Some(40), Some(40)]);
+        // exact matches do not count towards the limit
+        assert_eq!(ts.reindex(&index, FillPolicy::ForwardFill{ limit: Some(2) }).values, vec![None, None, Some(10), Some(10), Some(20), Some(20), None, Some(40), Some(40)]);
+        assert_eq!(ts.reindex(&index, FillPolicy::BackwardFill{ limit: None }).values, vec![Some(10), Some(10), Some(10), Some(20), Some(40), Some(40), Some(40), Some(40), None]);
+        assert_eq!(ts.reindex(&index, FillPolicy::BackwardFill{ limit: Some(1) }).values, vec![None, Some(10), Some(10), Some(20), None, None, Some(40), Some(40), None]);
+        // 3 is as close to 2 as it is to 4 so the prior point wins
+        assert_eq!(ts.reindex(&index, FillPolicy::Nearest{ tolerance: None }).values, vec![Some(10), Some(10), Some(10), Some(10), Some(20), Some(20), Some(40), Some(40), Some(40)]);
+        assert_eq!(ts.reindex(&index, FillPolicy::Nearest{ tolerance: Some(1) }).values, vec![None, Some(10), Some(10), Some(10), Some(20), None, Some(40), Some(40), None]);
+        assert_eq!(ts.reindex_interpolated(&index).values, vec![None, None, Some(10), Some(15), Some(25), Some(30), Some(35), Some(40), None]);
+        assert_eq!(ts.reindex(&index, FillPolicy::None).timeindicies, index);
+
+        let empty: TimeSeries<i32,i32> = TimeSeries::empty();
+        assert_eq!(empty.reindex_interpolated(&index).values, vec![None; 9]);
+        assert!(ts.reindex(&HashableIndex::new(vec![]), FillPolicy::None).is_empty());
+
+        // values that can not be interpolated can still be conformed with the other policies
+        let names = TimeSeries::from_vecs(vec![2, 4, 8], vec!["a".to_string(), "b".to_string(), "c".to_string()]).unwrap();
+        let some = |x: &str| Some(x.to_string());
+        assert_eq!(names.reindex(&index, FillPolicy::None).values, vec![None, None, some("a"), None, None, None, None, some("c"), None]);
+        assert_eq!(names.reindex(&index, FillPolicy::ForwardFill{ limit: Some(1) }).values, vec![None, None, some("a"), some("a"), some("b"), None, None, some("c"), some("c")]);
+        assert_eq!(names.reindex(&index, FillPolicy::BackwardFill{ limit: None }).values, vec![some("a"), some("a"), some("a"), some("b"), some("c"), some("c"), some("c"), some("c"), None]);
+        assert_eq!(names.reindex(&index, FillPolicy::Nearest{ tolerance: Some(1) }).values, vec![None, some("a"), some("a"), some("a"), some("b"), None, some("c"), some("c"), None]);
+    }
+
+    #[test]
+    fn test_upsample(){
+        let index = HashableIndex::from_int_stamps(vec![0, 90, 150]).into_values();
+        let ts = TimeSeries::from_vecs(index, vec![0.0, 3.0, 5.0]).unwrap();
+        let tsres = ts.upsample_interpolated(Duration::seconds(30)).unwrap();
+        assert_eq!(tsres.timeindicies, HashableIndex::from_int_stamps(vec![0, 30, 60, 90, 120, 150]));
+        assert_eq!(tsres.values, vec![Some(0.0), Some(1.0), Some(2.0), Some(3.0), Some(4.0), Some(5.0)]);
+
+        let tsres = ts.upsample(Duration::minutes(1), FillPolicy::ForwardFill{ limit: None }).unwrap();
+        assert_eq!(tsres.timeindicies, HashableIndex::from_int_stamps(vec![0, 60, 120]));
+        assert_eq!(tsres.values, vec![Some(0.0), Some(0.0), Some(3.0)]);
+
+        let saturated = TimeSeries::from_vecs(vec![u128::MAX - 1, u128::MAX], vec![1, 2]).unwrap();
+        assert_eq!(saturated.upsample(1, FillPolicy::None).unwrap().values, vec![Some(1), Some(2)]);
+
+        let names = TimeSeries::from_vecs(vec![0i64, 3], vec!["open", "close"]).unwrap();
+        assert_eq!(names.upsample(1, FillPolicy::ForwardFill{ limit: None }).unwrap().values, vec![Some("open"), Some("open"), Some("open"), Some("close")]);
+
+        assert!(matches!(ts.upsample(Duration::zero(), FillPolicy::None), Err(TsxError::InvalidParameter{ name: "step", .. })));
+        assert!(ts.upsample_interpolated(Duration::seconds(-30)).is_err());
+        assert!(TimeSeries::<i64,f64>::empty().upsample(0, FillPolicy::None).is_err());
+    }
+
+    #[test]
+    fn test_missing_values(){
+        let ts = TimeSeries::from_vecs(vec![0, 1, 2, 3, 4, 6, 7], vec![None, Some(1), None, None, None, Some(9), None]).unwrap();
+        assert_eq!(ts.ffill(None).values, vec![None, Some(1), Some(1), Some(1), Some(1), Some(9), Some(9)]);
+        assert_eq!(ts.ffill(Some(2)).values, vec![None, Some(1), Some(1), Some(1), None, Some(9), Some(9)]);
+        assert_eq!(ts.bfill(None).values, vec![Some(1), Some(1), Some(9), Some(9), Some(9), Some(9), None]);
+        assert_eq!(ts.bfill(Some(1)).values, vec![Some(1), Some(1), None, None, Some(9), Some(9), None]);
+        assert_eq!(ts.fillna(0).values, vec![0, 1, 0, 0, 0, 9, 0]);
+        let dropped = ts.dropna();
+        assert_eq!(dropped.timeindicies.values(), vec![1, 6]);
+        assert_eq!(dropped.values, vec![1, 9]);
+
+        assert_eq!(ts.interpolate(InterpolationMode::Linear).values, vec![None, Some(1), Some(3), Some(5), Some(7), Some(9), None]);
+        // the gap from 1 to 6 is 5 keys wide
+        assert_eq!(ts.interpolate(InterpolationMode::Time).values, vec![None, Some(1), Some(3), Some(4), Some(6), Some(9), None]);
+        assert_eq!(ts.interpolate(InterpolationMode::Nearest).values, vec![None, Some(1), Some(1), Some(1), Some(9), Some(9), None]);
+        assert_eq!(ts.interpolate(InterpolationMode::Previous), ts.ffill(None));
+
+        let empty: TimeSeries<i32,Option<f64>> = TimeSeries::empty();
+        assert!(empty.interpolate(InterpolationMode::Linear).is_empty());
+        assert!(empty.dropna().is_empty());
+    }
+
+    #[test]
+    fn test_mutation(){
+        let mut ts = TimeSeries::from_vecs(vec![2, 4], vec![2, 4]).unwrap();
+        ts.push(6, 6).unwrap();
+        assert!(matches!(ts.push(6, 7), Err(TsxError::DuplicateTimestamp{ position: 3 })));
+        assert!(matches!(ts.push(5, 5), Err(TsxError::NonMonotonicIndex{ position: 3 })));
+
+        ts.insert(3, 3, DuplicatePolicy::Error).unwrap();
+        ts.insert(0, 0, DuplicatePolicy::Error).unwrap();
+        assert!(matches!(ts.insert(4, 40, DuplicatePolicy::Error), Err(TsxError::DuplicateTimestamp{ position: 3 })));
+        ts.insert(4, 40, DuplicatePolicy::KeepFirst).unwrap();
+        assert_eq!(ts.at(4), Some(4));
+        ts.insert(4, 40, DuplicatePolicy::KeepLast).unwrap();
+        assert_eq!(ts.timeindicies.values(), vec![0, 2, 3, 4, 6]);
+        assert_eq!(ts.values, vec![0, 2, 3, 40, 6]);
+
+        // a failed extend leaves the series untouched
+        let bad = vec![TimeSeriesDataPoint::new(7, 7), TimeSeriesDataPoint::new(9, 9), TimeSeriesDataPoint::new(8, 8)];
+        assert!(matches!(ts.extend(bad), Err(TsxError::NonMonotonicIndex{ position: 7 })));
+        assert_eq!(ts.len(), 5);
+        ts.extend(vec![TimeSeriesDataPoint::new(7, 7), TimeSeriesDataPoint::new(9, 9)]).unwrap();
+        assert!(ts.timeindicies.is_monotonic());
+
+        assert_eq!(ts.remove(&3), Some(3));
+        assert_eq!(ts.remove(&3), None);
+        ts.truncate_before(&2);
+        ts.truncate_after(&8);
+        assert_eq!(ts.timeindicies.values(), vec![2, 4, 6, 7]);
+        ts.retain(|ts, v| ts % 2 == 0 && *v < 10);
+        assert_eq!(ts.timeindicies.values(), vec![2, 6]);
+        assert_eq!(ts.values, vec![2, 6]);
+
+        let mut empty: TimeSeries<i32,i32> = TimeSeries::empty();
+        empty.truncate_after(&1);
+        empty.push(1, 1).unwrap();
+        assert_eq!(empty.len(), 1);
+    }
+
+    #[test]
+    fn test_accessors(){
+        let mut ts = TimeSeries::from_vecs(vec![1, 2, 3], vec![1.0, 2.0, 3.0]).unwrap();
+        ts.values_mut()[1] = 20.0;
+        assert_eq!(ts.as_slices(), (&[1, 2, 3][..], &[1.0, 20.0, 3.0][..]));
+        assert_eq!(ts.index().values(), ts.as_slices().0);
+        let (index, values) = ts.into_parts();
+        assert_eq!(index.into_values(), vec![1, 2, 3]);
+        assert_eq!(values, vec![1.0, 20.0, 3.0]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "sorted and unique")]
+    fn test_invariant_check(){
+        // the unchecked constructors can still build a broken series, the next mutation catches it in debug builds
+        let mut ts = TimeSeries::from_vecs_unchecked(HashableIndex::new(vec![3, 1]), vec![3, 1]);
+        ts.push(4, 4).unwrap();
+    }
+
+    #[test]
+    fn test_duplicate_policies(){
+        let index = vec![1, 2, 2, 2, 3];
+        let values = vec![1, 2, 3, 4, 5];
+        assert!(matches!(TimeSeries::from_vecs_with_policy(index.clone(), values.clone(), DuplicatePolicy::Error), Err(TsxError::DuplicateTimestamp{ position: 2 })));
+        assert!(matches!(TimeSeries::from_vecs_with_policy(vec![2, 1], vec![1, 2], DuplicatePolicy::KeepLast), Err(TsxError::NonMonotonicIndex{ position: 1 })));
+        assert!(matches!(TimeSeries::from_vecs_with_policy(vec![1], vec![1, 2], DuplicatePolicy::KeepLast), Err(TsxError::LengthMismatch{ .. })));
+
+        let first = TimeSeries::from_vecs_with_policy(index.clone(), values.clone(), DuplicatePolicy::KeepFirst).unwrap();
+        assert_eq!(first.index().values(), vec![1, 2, 3]);
+        assert_eq!(first.values(), vec![1, 2, 5]);
+        let last = TimeSeries::from_vecs_with_policy(index.clone(), values.clone(), DuplicatePolicy::KeepLast).unwrap();
+        assert_eq!(last.values(), vec![1, 4, 5]);
+        let summed = TimeSeries::from_vecs_with_policy(index.clone(), values.clone(), DuplicatePolicy::aggregate(|x| x.iter().sum())).unwrap();
+        assert_eq!(summed.values(), vec![1, 9, 5]);
+        // the aggregation can capture its environment
+        let weight = 10;
+        let weighted = TimeSeries::from_vecs_with_policy(index, values, DuplicatePolicy::aggregate(move |x| x.iter().sum::<i32>() * weight)).unwrap();
+        assert_eq!(weighted.values(), vec![1, 90, 5]);
+
+        // unordered points keep the order they came in within a timestamp
+        let data = vec![TimeSeriesDataPoint::new(2, 20), TimeSeriesDataPoint::new(1, 10), TimeSeriesDataPoint::new(2, 21), TimeSeriesDataPoint::new(2, 22)];
+        let ts = TimeSeries::from_tsdatapoints_with_policy(data.clone(), DuplicatePolicy::KeepLast).unwrap();
+        assert_eq!(ts.values(), vec![10, 22]);
+        let ts = TimeSeries::from_tsdatapoints_with_policy(data.clone(), DuplicatePolicy::Aggregate(Box::new(|x| x[0] * 100 + x[1]))).unwrap();
+        assert_eq!(ts.values(), vec![10, 2021]);
+
+        let unchecked = TimeSeries::from_tsdatapoints_unchecked(data);
+        assert_eq!(unchecked.dedup_by(DuplicatePolicy::KeepFirst).unwrap().values(), vec![10, 20]);
+        assert!(matches!(unchecked.dedup_by(DuplicatePolicy::Error), Err(TsxError::DuplicateTimestamp{ .. })));
+
+        let mut ts = TimeSeries::from_vecs(vec![1, 2], vec![1, 2]).unwrap();
+        ts.insert(2, 5, DuplicatePolicy::aggregate(|x| x.iter().sum())).unwrap();
+        assert_eq!(ts.values(), vec![1, 7]);
+
+        assert_eq!(DuplicatePolicy::<i32>::KeepFirst, DuplicatePolicy::KeepFirst);
+        assert_ne!(DuplicatePolicy::<i32>::KeepFirst, DuplicatePolicy::KeepLast);
+        assert_ne!(DuplicatePolicy::aggregate(|x: &[i32]| x[0]), DuplicatePolicy::aggregate(|x: &[i32]| x[0]));
+        assert_eq!(format!("{:?}", DuplicatePolicy::aggregate(|x: &[i32]| x[0])), "Aggregate(..)");
+    }
+
+    #[test]
+    fn test_lookups(){
+        let index: Vec<i32> = (0..50).map(|i| i * 3 + i % 2).collect();
+        let ts = TimeSeries::from_vecs(index.clone(), (0..50).collect()).unwrap();
+        let queries: Vec<i32> = (-3..155).collect();
+        for q in queries.iter() {
+            let prior = index.iter().rposition(|x| x <= q).filter(|_| q <= index.last().unwrap());
+            assert_eq!(ts.at_or_first_prior(*q), prior.map(|p| p as i32));
+            let after = index.iter().position(|x| x >= q);
+            assert_eq!(ts.at_or_first_after(*q), after.map(|p| p as i32));
+            let nearest = (0..50).min_by_key(|p| (index[*p as usize] - q).abs()).unwrap();
+            assert_eq!(ts.nearest(*q), Some(nearest));
+        }
+
+        let exact: Vec<Option<i32>> = queries.iter().map(|q| ts.at(*q)).collect();
+        assert_eq!(ts.at_many(&queries), exact);
+        let asof: Vec<Option<i32>> = queries.iter().map(|q| ts.at_or_first_prior(*q)).collect();
+        assert_eq!(ts.asof_many(&queries), asof);
+
+        // unsorted queries are still answered correctly
+        let shuffled = [40, 3, 150, -1, 4, 4, 0];
+        assert_eq!(ts.at_many(&shuffled), shuffled.iter().map(|q| ts.at(*q)).collect::<Vec<Option<i32>>>());
+        assert_eq!(ts.asof_many(&shuffled), vec![Some(13), Some(0), None, None, Some(1), Some(1), Some(0)]);
+
+        let empty: TimeSeries<i32,i32> = TimeSeries::empty();
+        assert_eq!(empty.nearest(1), None);
+        assert_eq!(empty.at_or_first_after(1), None);
+        assert_eq!(empty.asof_many(&[1]), vec![None]);
+    }
 }
@@ -1,11 +1,14 @@
 //! # TimeSeries Iterators
 use std::cmp;
 use std::hash::Hash;
+use std::ops::Range;
 
 use serde::{Serialize};
 
 use crate::data_elements::TimeSeriesDataPoint;
 use crate::timeseries::TimeSeries;
+use crate::algo::time_distance::TimeDistance;
+use crate::error::TsxError;
 
 /// An iterator that gaurentees proper ordering of a TimeSeries. if this iterator encounters a non monitonically increasing value it stops evaluating
 pub struct OrderedTimeSeriesIter<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> {
@@ -31,17 +34,17 @@ impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> Iterator
         if self.index  < self.ts.len() {
             self.index += 1;
             let rval = Some(TimeSeriesDataPoint::new(
-                self.ts.timeindicies[self.index - 1].clone(),
-                self.ts.values[self.index - 1].clone()
+                self.ts.index()[self.index - 1].clone(),
+                self.ts.values()[self.index - 1].clone()
             ));
             match self.priorts.is_none() {
                 true => {
-                    self.priorts = Some(&self.ts.timeindicies[self.index - 1]);
+                    self.priorts = Some(&self.ts.index()[self.index - 1]);
                     rval
                 },
-                false => match self.ts.timeindicies[self.index - 1] >= *self.priorts.unwrap(){
+                false => match self.ts.index()[self.index - 1] >= *self.priorts.unwrap(){
                     true => {
-                        self.priorts = Some(&self.ts.timeindicies[self.index - 1]);
+                        self.priorts = Some(&self.ts.index()[self.index - 1]);
                         rval
                     },
                     false => {
@@ -78,17 +81,17 @@ impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> Iterator
         if self.index  < self.ts.len() {
             self.index += 1;
             let rval = Some(TimeSeriesDataPoint::new(
-                &self.ts.timeindicies[self.index - 1],
-                &self.ts.values[self.index - 1]
+                &self.ts.index()[self.index - 1],
+                &self.ts.values()[self.index - 1]
             ));
             match self.priorts.is_none() {
                 true => {
-                    self.priorts = Some(&self.ts.timeindicies[self.index - 1]);
+                    self.priorts = Some(&self.ts.index()[self.index - 1]);
                     rval
                 },
-                false => match self.ts.timeindicies[self.index - 1] >= *self.priorts.unwrap(){
+                false => match self.ts.index()[self.index - 1] >= *self.priorts.unwrap(){
                     true => {
-                        self.priorts = Some(&self.ts.timeindicies[self.index - 1]);
+                        self.priorts = Some(&self.ts.index()[self.index - 1]);
                         rval
                     },
                     false => {
@@ -124,8 +127,8 @@ impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone > Iterato
         if self.index  < self.ts.len() {
             self.index += 1;
             Some(TimeSeriesDataPoint::new(
-                self.ts.timeindicies[self.index - 1].clone(),
-                self.ts.values[self.index - 1].clone()
+                self.ts.index()[self.index - 1].clone(),
+                self.ts.values()[self.index - 1].clone()
             ))        
         } else {
             None
@@ -155,8 +158,8 @@ impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone > Iterato
         if self.index  < self.ts.len() {
             self.index += 1;
             Some(TimeSeriesDataPoint::new(
-                &self.ts.timeindicies[self.index - 1],
-                &self.ts.values[self.index - 1]
+                &self.ts.index()[self.index - 1],
+                &self.ts.values()[self.index - 1]
             ))        
         } else {
             None
@@ -221,8 +224,8 @@ impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> Iterator
             let curidx = cmp::max(tidx, valueidx);
             if  curidx < tsbounds {    
                 Some(TimeSeriesDataPoint::new(
-                    self.ts.timeindicies[tidx].clone(),
-                    self.ts.values[valueidx].clone()))
+                    self.ts.index()[tidx].clone(),
+                    self.ts.values()[valueidx].clone()))
             } else {
                 None
             }
@@ -234,38 +237,46 @@ impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone> Iterator
     }
 }
 /// an iterator that represents a rolling operation on a Timeseries. Data in the window is held in a buffer that gets reduced according the the transform func.
-pub struct RollingTimeSeriesIter<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone> {
+pub struct RollingTimeSeriesIter<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, F: FnMut(&Vec<T>)->TReduce> {
     ts: &'a TimeSeries<TDate,T>,
     index: usize,
-    transform_func: fn(&Vec<T>)->TReduce,
+    transform_func: F,
     buffer: Vec<T>,
 }
 
-impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone> RollingTimeSeriesIter<'a, TDate, T, TReduce>{
-    pub fn new(ts: &'a TimeSeries<TDate,T>, window_size: usize,transform_func: fn(&Vec<T>)->TReduce) -> RollingTimeSeriesIter<'a, TDate, T, TReduce>{
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, F: FnMut(&Vec<T>)->TReduce> RollingTimeSeriesIter<'a, TDate, T, TReduce, F>{
+    /// a window of size 0 or one that is larger than the series gives an empty iterator
+    pub fn new(ts: &'a TimeSeries<TDate,T>, window_size: usize,transform_func: F) -> RollingTimeSeriesIter<'a, TDate, T, TReduce, F>{
+        if window_size == 0 || window_size > ts.len() {
+            return RollingTimeSeriesIter {
+                ts,
+                index: ts.len(),
+                transform_func,
+                buffer: Vec::new()
+            };
+        }
         let init_index = window_size - 1;
         RollingTimeSeriesIter {
             ts,
             index: init_index,
             transform_func,
-            buffer: ts.values[0..(window_size-1)].to_vec()
+            buffer: ts.values()[0..(window_size-1)].to_vec()
         }
     }
 }
 
-impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone> Iterator for RollingTimeSeriesIter<'a, TDate, T, TReduce> {
+impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, F: FnMut(&Vec<T>)->TReduce> Iterator for RollingTimeSeriesIter<'a, TDate, T, TReduce, F> {
     type Item = TimeSeriesDataPoint<TDate,TReduce>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index  < self.ts.len() {
             self.index += 1;
-            let rv = self.ts.values[self.index - 1].clone();
-            let func = self.transform_func;            
+            let rv = self.ts.values()[self.index - 1].clone();
             self.buffer.push(rv);
-            let newv = func(&self.buffer);
+            let newv = (self.transform_func)(&self.buffer);
             self.buffer.remove(0);
             Some(TimeSeriesDataPoint::new(
-                self.ts.timeindicies[self.index - 1].clone(),
+                self.ts.index()[self.index - 1].clone(),
                 newv
             ))
         } else {
@@ -275,25 +286,34 @@ impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce:
 }
 
 /// an iterator that represents a rolling operation on a Timeseries. the transform value is computed according the update and decrement functions, i.e. if you wanted to get the rolling sum you would make it such that update_func => existing value + next value and decrement_func => existing value - last value
-pub struct RollingTimeSeriesIterWithUpdate<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T:Clone, TReduce: Clone> {
+pub struct RollingTimeSeriesIterWithUpdate<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T:Clone, TReduce: Clone, FUpdate: FnMut(Option<TReduce>, &T)->Option<TReduce>, FDecrement: FnMut(Option<TReduce>, &T)->Option<TReduce>> {
     ts: &'a TimeSeries<TDate,T>,
     index: usize,
     ref_value: Option<TReduce>,
-    last_value: &'a T,
-    update_func: fn(Option<TReduce>, &T)->Option<TReduce>,
-    decrement_func: fn(Option<TReduce>, &T)->Option<TReduce>,
+    update_func: FUpdate,
+    decrement_func: FDecrement,
     window_size: usize
 }
 
-impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone> RollingTimeSeriesIterWithUpdate<'a, TDate, T, TReduce>{
-    pub fn new(ts: &'a TimeSeries<TDate,T>, window_size: usize, update_func: fn(Option<TReduce>, &T)->Option<TReduce>, decrement_func: fn(Option<TReduce>, &T)->Option<TReduce>) -> RollingTimeSeriesIterWithUpdate<'a, TDate, T, TReduce>{
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, FUpdate: FnMut(Option<TReduce>, &T)->Option<TReduce>, FDecrement: FnMut(Option<TReduce>, &T)->Option<TReduce>> RollingTimeSeriesIterWithUpdate<'a, TDate, T, TReduce, FUpdate, FDecrement>{
+    /// a window of size 0 or one that is larger than the series gives an empty iterator
+    pub fn new(ts: &'a TimeSeries<TDate,T>, window_size: usize, mut update_func: FUpdate, decrement_func: FDecrement) -> RollingTimeSeriesIterWithUpdate<'a, TDate, T, TReduce, FUpdate, FDecrement>{
+        if window_size == 0 || window_size > ts.len() {
+            return RollingTimeSeriesIterWithUpdate {
+                ts,
+                index: ts.len(),
+                ref_value: None,
+                update_func,
+                decrement_func,
+                window_size
+            };
+        }
         let init_index = window_size - 1;
-        let initval = ts.values[0..(window_size)].to_vec().iter().fold(None,update_func);
+        let initval = ts.values()[0..init_index].iter().fold(None,&mut update_func);
         RollingTimeSeriesIterWithUpdate {
             ts,
             index: init_index,
             ref_value : initval,
-            last_value: &ts.values[window_size-1],
             update_func,
             decrement_func,
             window_size
@@ -301,21 +321,20 @@ impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce
     }
 }
 
-impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone> Iterator for RollingTimeSeriesIterWithUpdate<'a, TDate, T, TReduce> {
+impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, FUpdate: FnMut(Option<TReduce>, &T)->Option<TReduce>, FDecrement: FnMut(Option<TReduce>, &T)->Option<TReduce>> Iterator for RollingTimeSeriesIterWithUpdate<'a, TDate, T, TReduce, FUpdate, FDecrement> {
     type Item = TimeSeriesDataPoint<TDate,TReduce>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index  < self.ts.len() {
             self.index += 1;
-            let rv = self.ts.values[self.index - 1].clone();
-            let up_func = self.update_func;
-            let dec_func = self.decrement_func;
-            self.ref_value = up_func(self.ref_value.clone(),&rv);
-            self.ref_value = dec_func(self.ref_value.clone(),&self.last_value);
-            self.last_value = &self.ts.values[self.index - self.window_size];
+            let rv = &self.ts.values()[self.index - 1];
+            self.ref_value = (self.update_func)(self.ref_value.clone(),rv);
+            if self.index > self.window_size {
+                self.ref_value = (self.decrement_func)(self.ref_value.clone(),&self.ts.values()[self.index - 1 - self.window_size]);
+            }
             match self.ref_value.is_some() { 
             true => Some(TimeSeriesDataPoint::new(
-                self.ts.timeindicies[self.index - 1].clone(),
+                self.ts.index()[self.index - 1].clone(),
                 self.ref_value.clone().unwrap()
             )),
             false => None
@@ -326,41 +345,566 @@ impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce:
     }
 }
 
+/// RollingOptions control the count based windows of `TimeSeries::apply_rolling_with`
+#[derive(Clone,Copy,Debug,PartialEq,Default)]
+pub struct RollingOptions {
+    /// report a window at its center point instead of at its last point, for even sizes the window has one more point before the center than after it
+    pub center: bool,
+    /// the least number of points a window needs to produce a value, windows at the edges of the series can hold less than the window size. Defaults to the window size
+    pub min_periods: Option<usize>,
+    /// emit None for the points whose window has less than `min_periods` points instead of skipping them, so that the output keeps the index of the input
+    pub placeholders: bool
+}
+
+/// an iterator that represents a rolling operation on a Timeseries with a trailing or centered window that can produce values for partial windows, see `RollingOptions`.
+/// Data in the window is passed to the transform func as a slice
+pub struct AlignedRollingTimeSeriesIter<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, F: FnMut(&[T])->TReduce> {
+    ts: &'a TimeSeries<TDate,T>,
+    index: usize,
+    window_size: usize,
+    options: RollingOptions,
+    transform_func: F
+}
+
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, F: FnMut(&[T])->TReduce> AlignedRollingTimeSeriesIter<'a, TDate, T, TReduce, F>{
+    pub fn new(ts: &'a TimeSeries<TDate,T>, window_size: usize, options: RollingOptions, transform_func: F) -> AlignedRollingTimeSeriesIter<'a, TDate, T, TReduce, F>{
+        AlignedRollingTimeSeriesIter {
+            ts,
+            index: 0,
+            window_size,
+            options,
+            transform_func
+        }
+    }
+
+    /// the positions of the window reported at `pos`, clipped to the series
+    fn window_at(&self, pos: usize) -> Range<usize> {
+        let (before, after) = match self.options.center {
+            true => (self.window_size / 2, self.window_size.saturating_sub(1) / 2),
+            false => (self.window_size.saturating_sub(1), 0)
+        };
+        let end = cmp::min(pos + after + 1, self.ts.len());
+        match self.window_size {
+            0 => pos..pos,
+            _ => pos.saturating_sub(before)..end
+        }
+    }
+}
+
+impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, F: FnMut(&[T])->TReduce> Iterator for AlignedRollingTimeSeriesIter<'a, TDate, T, TReduce, F> {
+    type Item = TimeSeriesDataPoint<TDate,Option<TReduce>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let min_periods = self.options.min_periods.unwrap_or(self.window_size);
+        while self.index < self.ts.len() {
+            self.index += 1;
+            let pos = self.index - 1;
+            let window = self.window_at(pos);
+            let newv = match window.len() >= min_periods {
+                true => Some((self.transform_func)(&self.ts.values()[window])),
+                false => None
+            };
+            if newv.is_some() || self.options.placeholders {
+                return Some(TimeSeriesDataPoint::new(
+                    self.ts.index()[pos].clone(),
+                    newv
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// WindowClosed describes which ends of a time based window are included, i.e. with `Right` the window of a point at t is (t - span, t]
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum WindowClosed{ Right, Left, Both, Neither}
+
+/// This trait defines the contract for the windows of the built-in rolling aggregations, see `CountWindow` and `TimeWindow`
+pub trait RollingWindow<TDate> {
+    /// move the window so that it is anchored at position `pos` of the index and return the positions it covers, positions are visited in increasing order
+    fn advance_to(&mut self, index: &[TDate], pos: usize) -> Range<usize>;
+    /// whether the window covers enough points to produce a value
+    fn is_complete(&self, window: &Range<usize>) -> bool;
+}
+
+/// A window of the last `size` points, there is no value until the window is full (like `apply_rolling`)
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct CountWindow {
+    size: usize
+}
+
+impl CountWindow {
+    pub fn new(size: usize) -> CountWindow {
+        CountWindow { size }
+    }
+}
+
+impl<TDate> RollingWindow<TDate> for CountWindow {
+    fn advance_to(&mut self, _index: &[TDate], pos: usize) -> Range<usize> {
+        (pos + 1 - cmp::min(self.size, pos + 1))..(pos + 1)
+    }
+
+    fn is_complete(&self, window: &Range<usize>) -> bool {
+        window.len() == self.size
+    }
+}
+
+/// A window over a time span, `closed` sets which ends of it are included and there is no value while it covers less than `min_observations` points
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct TimeWindow<TDelta> {
+    span: TDelta,
+    closed: WindowClosed,
+    min_observations: usize,
+    pub(crate) start: usize,
+    pub(crate) end: usize
+}
+
+impl<TDelta: Copy + Ord> TimeWindow<TDelta> {
+    pub fn new(span: TDelta, closed: WindowClosed, min_observations: usize) -> TimeWindow<TDelta> {
+        TimeWindow { span, closed, min_observations, start: 0, end: 0 }
+    }
+
+    /// move the window so that it is anchored at position `pos`, positions have to be visited in increasing order
+    pub(crate) fn move_to<TDate: TimeDistance<Delta = TDelta>>(&mut self, index: &[TDate], pos: usize) {
+        let anchor = &index[pos];
+        self.end = match self.closed {
+            WindowClosed::Right | WindowClosed::Both => pos + 1,
+            WindowClosed::Left | WindowClosed::Neither => pos
+        };
+        let include_left = matches!(self.closed, WindowClosed::Left | WindowClosed::Both);
+        while self.start < pos {
+            let dist = anchor.delta(&index[self.start]);
+            let inside = match include_left {
+                true => dist <= self.span,
+                false => dist < self.span
+            };
+            if inside {
+                break;
+            }
+            self.start += 1;
+        }
+        self.start = cmp::min(self.start, self.end);
+    }
+
+    pub(crate) fn is_ready(&self) -> bool {
+        self.end - self.start >= self.min_observations
+    }
+}
+
+impl<TDate: TimeDistance> RollingWindow<TDate> for TimeWindow<TDate::Delta> {
+    fn advance_to(&mut self, index: &[TDate], pos: usize) -> Range<usize> {
+        self.move_to(index, pos);
+        self.start..self.end
+    }
+
+    fn is_complete(&self, window: &Range<usize>) -> bool {
+        window.len() >= self.min_observations
+    }
+}
+
+/// an iterator that represents a rolling operation over a time span on a Timeseries. Data in the window is passed to the transform func as a slice, points whose window has less than `min_observations` points are skipped
+pub struct TimeRollingTimeSeriesIter<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord + TimeDistance, T: Clone, TReduce: Clone, F: FnMut(&[T])->TReduce> {
+    ts: &'a TimeSeries<TDate,T>,
+    index: usize,
+    window: TimeWindow<TDate::Delta>,
+    transform_func: F
+}
+
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord + TimeDistance, T: Clone, TReduce: Clone, F: FnMut(&[T])->TReduce> TimeRollingTimeSeriesIter<'a, TDate, T, TReduce, F>{
+    pub fn new(ts: &'a TimeSeries<TDate,T>, span: TDate::Delta, closed: WindowClosed, min_observations: usize, transform_func: F) -> TimeRollingTimeSeriesIter<'a, TDate, T, TReduce, F>{
+        TimeRollingTimeSeriesIter {
+            ts,
+            index: 0,
+            window: TimeWindow::new(span, closed, min_observations),
+            transform_func
+        }
+    }
+}
+
+impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord + TimeDistance, T: Clone, TReduce: Clone, F: FnMut(&[T])->TReduce> Iterator for TimeRollingTimeSeriesIter<'a, TDate, T, TReduce, F> {
+    type Item = TimeSeriesDataPoint<TDate,TReduce>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.ts.len() {
+            self.index += 1;
+            let pos = self.index - 1;
+            self.window.move_to(self.ts.index().values(), pos);
+            if self.window.is_ready() {
+                let newv = (self.transform_func)(&self.ts.values()[self.window.start..self.window.end]);
+                return Some(TimeSeriesDataPoint::new(
+                    self.ts.index()[pos].clone(),
+                    newv
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// an iterator that represents a rolling operation over a time span on a Timeseries. the transform value is computed according the update and decrement functions in the same way as `RollingTimeSeriesIterWithUpdate`, 
+/// points whose window has less than `min_observations` points are skipped
+pub struct TimeRollingTimeSeriesIterWithUpdate<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord + TimeDistance, T:Clone, TReduce: Clone, FUpdate: FnMut(Option<TReduce>, &T)->Option<TReduce>, FDecrement: FnMut(Option<TReduce>, &T)->Option<TReduce>> {
+    ts: &'a TimeSeries<TDate,T>,
+    index: usize,
+    window: TimeWindow<TDate::Delta>,
+    ref_value: Option<TReduce>,
+    update_func: FUpdate,
+    decrement_func: FDecrement
+}
+
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord + TimeDistance, T: Clone, TReduce: Clone, FUpdate: FnMut(Option<TReduce>, &T)->Option<TReduce>, FDecrement: FnMut(Option<TReduce>, &T)->Option<TReduce>> TimeRollingTimeSeriesIterWithUpdate<'a, TDate, T, TReduce, FUpdate, FDecrement>{
+    pub fn new(ts: &'a TimeSeries<TDate,T>, span: TDate::Delta, closed: WindowClosed, min_observations: usize, update_func: FUpdate, decrement_func: FDecrement) -> TimeRollingTimeSeriesIterWithUpdate<'a, TDate, T, TReduce, FUpdate, FDecrement>{
+        TimeRollingTimeSeriesIterWithUpdate {
+            ts,
+            index: 0,
+            window: TimeWindow::new(span, closed, min_observations),
+            ref_value: None,
+            update_func,
+            decrement_func
+        }
+    }
+}
+
+impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord + TimeDistance, T: Clone, TReduce: Clone, FUpdate: FnMut(Option<TReduce>, &T)->Option<TReduce>, FDecrement: FnMut(Option<TReduce>, &T)->Option<TReduce>> Iterator for TimeRollingTimeSeriesIterWithUpdate<'a, TDate, T, TReduce, FUpdate, FDecrement> {
+    type Item = TimeSeriesDataPoint<TDate,TReduce>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.ts.len() {
+            self.index += 1;
+            let pos = self.index - 1;
+            let (old_start, old_end) = (self.window.start, self.window.end);
+            self.window.move_to(self.ts.index().values(), pos);
+            // points that enter the window are added before the ones that leave it are removed
+            for v in &self.ts.values()[old_end..self.window.end] {
+                self.ref_value = (self.update_func)(self.ref_value.clone(), v);
+            }
+            for v in &self.ts.values()[old_start..self.window.start] {
+                self.ref_value = (self.decrement_func)(self.ref_value.clone(), v);
+            }
+            if self.window.is_ready() {
+                if let Some(newv) = self.ref_value.clone() {
+                    return Some(TimeSeriesDataPoint::new(
+                        self.ts.index()[pos].clone(),
+                        newv
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// an iterator that represents an expanding (cumulative) operation on a Timeseries. the update function is given the running state and the next value, points for which the state is None are skipped
+pub struct ExpandingTimeSeriesIter<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, F: FnMut(Option<TReduce>, &T)->Option<TReduce>> {
+    ts: &'a TimeSeries<TDate,T>,
+    index: usize,
+    ref_value: Option<TReduce>,
+    update_func: F
+}
+
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, F: FnMut(Option<TReduce>, &T)->Option<TReduce>> ExpandingTimeSeriesIter<'a, TDate, T, TReduce, F>{
+    pub fn new(ts: &'a TimeSeries<TDate,T>, update_func: F) -> ExpandingTimeSeriesIter<'a, TDate, T, TReduce, F>{
+        ExpandingTimeSeriesIter {
+            ts,
+            index: 0,
+            ref_value: None,
+            update_func
+        }
+    }
+}
+
+impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, F: FnMut(Option<TReduce>, &T)->Option<TReduce>> Iterator for ExpandingTimeSeriesIter<'a, TDate, T, TReduce, F> {
+    type Item = TimeSeriesDataPoint<TDate,TReduce>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.ts.len() {
+            self.index += 1;
+            self.ref_value = (self.update_func)(self.ref_value.take(), &self.ts.values()[self.index - 1]);
+            if let Some(newv) = self.ref_value.clone() {
+                return Some(TimeSeriesDataPoint::new(
+                    self.ts.index()[self.index - 1].clone(),
+                    newv
+                ));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.ts.len() - self.index))
+    }
+}
+
+/// EwmDecay sets how fast the weights of an exponentially weighted statistic decay with each observation, it follows the pandas definitions, i.e. alpha = 2 / (span + 1).
+/// The constructors check the parameter, so a decay that exists is always valid
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct EwmDecay{ alpha: f64 }
+
+impl EwmDecay {
+    /// decay with the smoothing factor `alpha`, which has to be in (0, 1]
+    pub fn alpha(alpha: f64) -> Result<EwmDecay, TsxError> {
+        if alpha > 0.0 && alpha <= 1.0 {
+            Ok(EwmDecay{ alpha })
+        } else {
+            Err(TsxError::InvalidParameter{ name: "alpha", value: alpha.to_string(), expected: "in (0, 1]" })
+        }
+    }
+
+    /// decay over a span of observations, which has to be >= 1
+    pub fn span(span: f64) -> Result<EwmDecay, TsxError> {
+        if span >= 1.0 {
+            Ok(EwmDecay{ alpha: 2.0 / (span + 1.0) })
+        } else {
+            Err(TsxError::InvalidParameter{ name: "span", value: span.to_string(), expected: ">= 1" })
+        }
+    }
+
+    /// decay where a weight halves every `halflife` observations, which has to be > 0
+    pub fn halflife(halflife: f64) -> Result<EwmDecay, TsxError> {
+        if halflife > 0.0 {
+            Ok(EwmDecay{ alpha: 1.0 - (0.5f64.ln() / halflife).exp() })
+        } else {
+            Err(TsxError::InvalidParameter{ name: "halflife", value: halflife.to_string(), expected: "> 0" })
+        }
+    }
+
+    /// the smoothing factor alpha of the decay
+    pub fn smoothing_factor(&self) -> f64 {
+        self.alpha
+    }
+}
+
+/// exponentially weighted statistics of a series at a point. `var` is bias corrected, `var_biased` is not, all of them are NaN before the first (non NaN) observation
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct EwmStats {
+    pub mean: f64,
+    pub var: f64,
+    pub var_biased: f64
+}
+
+impl EwmStats {
+    /// bias corrected standard deviation
+    pub fn std(&self) -> f64 {
+        self.var.sqrt()
+    }
+}
+
+/// State of the exponentially weighted mean/covariance recursion, ported from the pandas implementation (with ignore_na = false)
+#[derive(Clone,Copy,Debug)]
+pub(crate) struct EwmState {
+    adjust: bool,
+    mean_x: f64,
+    mean_y: f64,
+    cov: f64,
+    sum_wt: f64,
+    sum_wt2: f64,
+    old_wt: f64,
+    nobs: usize
+}
+
+impl EwmState {
+    pub(crate) fn new(adjust: bool) -> EwmState {
+        EwmState { adjust, mean_x: f64::NAN, mean_y: f64::NAN, cov: 0.0, sum_wt: 1.0, sum_wt2: 1.0, old_wt: 1.0, nobs: 0 }
+    }
+
+    /// decay the weights seen so far by `factor` and add the observation (x, y), NaN observations only decay the weights
+    pub(crate) fn update(&mut self, factor: f64, x: f64, y: f64) {
+        let is_observation = !x.is_nan() && !y.is_nan();
+        if self.nobs == 0 {
+            if is_observation {
+                self.mean_x = x;
+                self.mean_y = y;
+                self.nobs = 1;
+            }
+            return;
+        }
+        self.sum_wt *= factor;
+        self.sum_wt2 *= factor * factor;
+        self.old_wt *= factor;
+        if is_observation {
+            let new_wt = match self.adjust {
+                true => 1.0,
+                false => 1.0 - factor
+            };
+            let (old_mean_x, old_mean_y) = (self.mean_x, self.mean_y);
+            let total_wt = self.old_wt + new_wt;
+            self.mean_x = (self.old_wt * old_mean_x + new_wt * x) / total_wt;
+            self.mean_y = (self.old_wt * old_mean_y + new_wt * y) / total_wt;
+            self.cov = (self.old_wt * (self.cov + (old_mean_x - self.mean_x) * (old_mean_y - self.mean_y))
+                + new_wt * (x - self.mean_x) * (y - self.mean_y)) / total_wt;
+            self.sum_wt += new_wt;
+            self.sum_wt2 += new_wt * new_wt;
+            self.old_wt += new_wt;
+            if !self.adjust {
+                self.sum_wt /= self.old_wt;
+                self.sum_wt2 /= self.old_wt * self.old_wt;
+                self.old_wt = 1.0;
+            }
+            self.nobs += 1;
+        }
+    }
+
+    pub(crate) fn mean(&self) -> f64 {
+        self.mean_x
+    }
+
+    pub(crate) fn cov(&self, bias: bool) -> f64 {
+        if self.nobs == 0 {
+            return f64::NAN;
+        }
+        if bias {
+            return self.cov;
+        }
+        let numerator = self.sum_wt * self.sum_wt;
+        let denominator = numerator - self.sum_wt2;
+        match denominator > 0.0 {
+            true => numerator / denominator * self.cov,
+            false => f64::NAN
+        }
+    }
+}
+
+/// an iterator that computes exponentially weighted statistics over a Timeseries, the decay func returns the factor the weights decay by between two consecutive timestamps (current, prior)
+pub struct EwmTimeSeriesIter<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone + Into<f64>, F: FnMut(&TDate,&TDate)->f64> {
+    ts: &'a TimeSeries<TDate,T>,
+    index: usize,
+    state: EwmState,
+    decay_func: F
+}
+
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone + Into<f64>, F: FnMut(&TDate,&TDate)->f64> EwmTimeSeriesIter<'a, TDate, T, F>{
+    pub fn new(ts: &'a TimeSeries<TDate,T>, adjust: bool, decay_func: F) -> EwmTimeSeriesIter<'a, TDate, T, F>{
+        EwmTimeSeriesIter {
+            ts,
+            index: 0,
+            state: EwmState::new(adjust),
+            decay_func
+        }
+    }
+}
+
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone + Into<f64>, F: FnMut(&TDate,&TDate)->f64> Iterator for EwmTimeSeriesIter<'a, TDate, T, F> {
+    type Item = TimeSeriesDataPoint<TDate,EwmStats>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.ts.len() {
+            self.index += 1;
+            let pos = self.index - 1;
+            let factor = match pos {
+                0 => 1.0,
+                _ => (self.decay_func)(&self.ts.index()[pos], &self.ts.index()[pos - 1])
+            };
+            let x: f64 = self.ts.values()[pos].clone().into();
+            self.state.update(factor, x, x);
+            Some(TimeSeriesDataPoint::new(
+                self.ts.index()[pos].clone(),
+                EwmStats { mean: self.state.mean(), var: self.state.cov(false), var_biased: self.state.cov(true) }
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.ts.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+/// This trait defines the contract for the built-in rolling aggregations, values enter and leave the window in the order of their position
+pub trait RollingAggregator<T> {
+    fn push(&mut self, pos: usize, value: &T);
+    fn pop(&mut self, pos: usize, value: &T);
+    /// the aggregate of the values currently in the window, None if it is not defined (i.e. the window has no values)
+    fn value(&self) -> Option<f64>;
+}
+
+/// an iterator that represents a built-in rolling aggregation on a Timeseries, the aggregator is updated with the points that enter and leave the window instead of recomputing it.
+/// points whose window is not complete or for which the aggregate is not defined are skipped
+pub struct RollingAggregationIter<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, W: RollingWindow<TDate>, A: RollingAggregator<T>> {
+    ts: &'a TimeSeries<TDate,T>,
+    index: usize,
+    window: W,
+    bounds: Range<usize>,
+    aggregator: A
+}
+
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, W: RollingWindow<TDate>, A: RollingAggregator<T>> RollingAggregationIter<'a, TDate, T, W, A>{
+    pub fn new(ts: &'a TimeSeries<TDate,T>, window: W, aggregator: A) -> RollingAggregationIter<'a, TDate, T, W, A>{
+        RollingAggregationIter {
+            ts,
+            index: 0,
+            window,
+            bounds: 0..0,
+            aggregator
+        }
+    }
+}
+
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, W: RollingWindow<TDate>, A: RollingAggregator<T>> Iterator for RollingAggregationIter<'a, TDate, T, W, A> {
+    type Item = TimeSeriesDataPoint<TDate,f64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.ts.len() {
+            self.index += 1;
+            let pos = self.index - 1;
+            let bounds = self.window.advance_to(self.ts.index().values(), pos);
+            // points that enter the window are added before the ones that leave it are removed
+            for p in self.bounds.end..bounds.end {
+                self.aggregator.push(p, &self.ts.values()[p]);
+            }
+            for p in self.bounds.start..bounds.start {
+                self.aggregator.pop(p, &self.ts.values()[p]);
+            }
+            let complete = self.window.is_complete(&bounds);
+            self.bounds = bounds;
+            if complete {
+                if let Some(newv) = self.aggregator.value() {
+                    return Some(TimeSeriesDataPoint::new(
+                        self.ts.index()[pos].clone(),
+                        newv
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
+
 
 /// an iterator that represents a skip operation on a Timeseries. Skips take the given span_size and apply a func on the two points on the edges to come up with a new values. You can express difference as a skip operations, e.g. `transform_func = |prior,next| next - prior`
-pub struct SkipApplyTimeSeriesIter<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T:Clone, TReduce: Clone> {
+pub struct SkipApplyTimeSeriesIter<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T:Clone, TReduce: Clone, F: FnMut(&T,&T)->TReduce> {
     ts: &'a TimeSeries<TDate,T>,
     index: usize,
     span_size: usize,
-    transform_func: fn(&T,&T)->TReduce,
+    transform_func: F,
     prior_value: T
 }
 
-impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone , TReduce: Clone> SkipApplyTimeSeriesIter<'a, TDate, T, TReduce>{
-    pub fn new(ts: &'a TimeSeries<TDate,T>, span_size: usize,transform_func: fn(&T,&T)->TReduce) -> SkipApplyTimeSeriesIter<'a, TDate, T, TReduce>{
+impl<'a, TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone , TReduce: Clone, F: FnMut(&T,&T)->TReduce> SkipApplyTimeSeriesIter<'a, TDate, T, TReduce, F>{
+    pub fn new(ts: &'a TimeSeries<TDate,T>, span_size: usize,transform_func: F) -> SkipApplyTimeSeriesIter<'a, TDate, T, TReduce, F>{
         let init_index = span_size;
         SkipApplyTimeSeriesIter {
             ts,
             index: init_index,
             span_size,
             transform_func,
-            prior_value: ts.values[0].clone()
+            prior_value: ts.values()[0].clone()
         }
     }
 }
 
-impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone> Iterator for SkipApplyTimeSeriesIter<'a, TDate, T, TReduce> {
+impl<'a,TDate: Serialize + Hash + Clone + cmp::Eq + cmp::Ord, T: Clone, TReduce: Clone, F: FnMut(&T,&T)->TReduce> Iterator for SkipApplyTimeSeriesIter<'a, TDate, T, TReduce, F> {
     type Item = TimeSeriesDataPoint<TDate,TReduce>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index - self.span_size + 1 < self.ts.len() {
             self.index += self.span_size;
-            let rv = self.ts.values[self.index - self.span_size].clone();
-            let func = self.transform_func;
-            let newv = func(&self.prior_value,&rv);
+            let rv = self.ts.values()[self.index - self.span_size].clone();
+            let newv = (self.transform_func)(&self.prior_value,&rv);
             self.prior_value = rv;
             Some(TimeSeriesDataPoint::new(
-                self.ts.timeindicies[self.index - self.span_size].clone(),
+                self.ts.index()[self.index - self.span_size].clone(),
                 newv
             ))
         } else {
@@ -552,4 +1096,193 @@ mod tests {
         assert_eq!(tsexp, ts_skipped);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_rolling_with_state() {
+        let values = vec![1.0, 4.0, 2.0, 9.0, 100.0];
+        let index = crate::index::HashableIndex::from_int_stamps((0..values.len()).map(|i| 60 * i as i64).collect());
+        let ts = TimeSeries::from_vecs(index.into_values(), values).unwrap();
+        let scale = 0.5;
+
+        let mut seen = Vec::new();
+        let rolled: TimeSeries<NaiveDateTime,f64> = ts.apply_rolling(2, |buffer| { seen.push(buffer.len()); buffer.iter().sum::<f64>() * scale }).collect();
+        assert_eq!(rolled.values(), vec![2.5, 3.0, 5.5, 54.5]);
+        assert_eq!(seen, vec![2, 2, 2, 2]);
+
+        let mut updates = 0;
+        let updated: TimeSeries<NaiveDateTime,f64> = ts.apply_updating_rolling(2, 
+            |prior, next| { updates += 1; Some(prior.unwrap_or(0.0) + next * scale) }, 
+            |next, prior| Some(next.unwrap_or(0.0) - prior * scale)).collect();
+        assert_eq!(rolled, updated);
+        // every point is added exactly once
+        assert_eq!(updates, 5);
+    }
+
+    #[test]
+    fn test_time_rolling() {
+        // irregular stamps
+        let ts = TimeSeries::from_vecs(vec![0i64, 1, 2, 5, 6, 10], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        let sum = |buffer: &[f64]| buffer.iter().sum::<f64>();
+
+        let right: TimeSeries<i64,f64> = ts.apply_rolling_by_time(3, WindowClosed::Right, 1, sum).collect();
+        assert_eq!(right.values(), vec![1.0, 3.0, 6.0, 4.0, 9.0, 6.0]);
+        let left: TimeSeries<i64,f64> = ts.apply_rolling_by_time(3, WindowClosed::Left, 1, sum).collect();
+        assert_eq!(left.index().values(), vec![1, 2, 5, 6]);
+        assert_eq!(left.values(), vec![1.0, 3.0, 3.0, 4.0]);
+        let both: TimeSeries<i64,f64> = ts.apply_rolling_by_time(3, WindowClosed::Both, 1, sum).collect();
+        assert_eq!(both.values(), vec![1.0, 3.0, 6.0, 7.0, 9.0, 6.0]);
+        let neither: TimeSeries<i64,f64> = ts.apply_rolling_by_time(3, WindowClosed::Neither, 1, sum).collect();
+        assert_eq!(neither.index().values(), vec![1, 2, 6]);
+        assert_eq!(neither.values(), vec![1.0, 3.0, 4.0]);
+
+        let min_two: TimeSeries<i64,f64> = ts.apply_rolling_by_time(3, WindowClosed::Right, 2, sum).collect();
+        assert_eq!(min_two.index().values(), vec![1, 2, 6]);
+
+        for closed in [WindowClosed::Right, WindowClosed::Left, WindowClosed::Both, WindowClosed::Neither].iter() {
+            let buffered: TimeSeries<i64,f64> = ts.apply_rolling_by_time(3, *closed, 1, sum).collect();
+            let updated: TimeSeries<i64,f64> = ts.apply_updating_rolling_by_time(3, *closed, 1, 
+                |prior: Option<f64>, next| Some(prior.unwrap_or(0.0) + next), 
+                |next: Option<f64>, prior| Some(next.unwrap_or(0.0) - prior)).collect();
+            assert_eq!(buffered, updated);
+        }
+    }
+
+    #[test]
+    fn test_time_rolling_chrono() {
+        let index = crate::index::HashableIndex::from_int_stamps(vec![0, 30, 60, 400, 410]);
+        let ts = TimeSeries::from_vecs(index.into_values(), vec![1, 1, 1, 1, 1]).unwrap();
+        let counts: TimeSeries<NaiveDateTime,usize> = ts.apply_rolling_by_time(chrono::Duration::minutes(5), WindowClosed::Right, 1, |buffer| buffer.len()).collect();
+        assert_eq!(counts.values(), vec![1, 2, 3, 1, 2]);
+    }
+
+    #[test]
+    fn test_expanding() {
+        let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4, 5], vec![1.0, -2.0, 3.0, f64::NAN, 5.0]).unwrap();
+        // count the non NaN values seen so far, nothing is emitted until the state exists
+        let counts: TimeSeries<i32,usize> = ts.apply_expanding(|acc: Option<usize>, x: &f64| match (acc, x.is_nan()) {
+            (None, true) => None,
+            (acc, true) => acc,
+            (acc, false) => Some(acc.unwrap_or(0) + 1)
+        }).collect();
+        assert_eq!(counts.values(), vec![1, 2, 3, 3, 4]);
+
+        let skipped: TimeSeries<i32,f64> = ts.apply_expanding(|acc: Option<f64>, x: &f64| match acc {
+            None if *x < 0.0 => None,
+            _ => Some(*x)
+        }).collect();
+        assert_eq!(skipped.index().values(), vec![1, 2, 3, 4, 5]);
+        let empty: TimeSeries<i32,f64> = TimeSeries::from_vecs(vec![-1, 0], vec![-1.0, -2.0]).unwrap().apply_expanding(|acc: Option<f64>, x: &f64| match acc {
+            None if *x < 0.0 => None,
+            _ => Some(*x)
+        }).collect();
+        assert!(empty.is_empty());
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_ewm() {
+        let ts = TimeSeries::from_vecs(vec![0, 1, 2], vec![1.0, 2.0, 3.0]).unwrap();
+        let adjusted: Vec<EwmStats> = ts.ewm(EwmDecay::alpha(0.5).unwrap(), true).map(|dp| dp.value).collect();
+        assert_close(adjusted[0].mean, 1.0);
+        assert!(adjusted[0].var.is_nan());
+        assert_close(adjusted[0].var_biased, 0.0);
+        assert_close(adjusted[1].mean, 5.0 / 3.0);
+        assert_close(adjusted[1].var_biased, 2.0 / 9.0);
+        assert_close(adjusted[1].var, 0.5);
+        // weights are 0.25, 0.5, 1
+        let mean = (0.25 * 1.0 + 0.5 * 2.0 + 3.0) / 1.75;
+        let var_biased = (0.25 * (1.0 - mean) * (1.0 - mean) + 0.5 * (2.0 - mean) * (2.0 - mean) + (3.0 - mean) * (3.0 - mean)) / 1.75;
+        assert_close(adjusted[2].mean, mean);
+        assert_close(adjusted[2].var_biased, var_biased);
+        assert_close(adjusted[2].var, var_biased * 1.75 * 1.75 / (1.75 * 1.75 - (0.0625 + 0.25 + 1.0)));
+
+        let unadjusted: Vec<f64> = ts.ewm(EwmDecay::alpha(0.5).unwrap(), false).map(|dp| dp.value.mean).collect();
+        assert_eq!(unadjusted, vec![1.0, 1.5, 2.25]);
+
+        assert_eq!(EwmDecay::span(3.0).unwrap().smoothing_factor(), 0.5);
+        assert_close(EwmDecay::halflife(1.0).unwrap().smoothing_factor(), 0.5);
+    }
+
+    #[test]
+    fn test_ewm_invalid_decay() {
+        assert!(matches!(EwmDecay::alpha(0.0), Err(TsxError::InvalidParameter{ name: "alpha", .. })));
+        assert!(matches!(EwmDecay::alpha(1.5), Err(TsxError::InvalidParameter{ name: "alpha", .. })));
+        assert!(EwmDecay::alpha(f64::NAN).is_err());
+        assert!(EwmDecay::alpha(1.0).is_ok());
+        assert!(matches!(EwmDecay::span(0.5), Err(TsxError::InvalidParameter{ name: "span", .. })));
+        assert!(matches!(EwmDecay::halflife(-1.0), Err(TsxError::InvalidParameter{ name: "halflife", .. })));
+
+        let ts = TimeSeries::from_vecs(crate::index::HashableIndex::from_int_stamps(vec![0, 10]).into_values(), vec![1.0, 2.0]).unwrap();
+        assert!(ts.ewm_by_time(chrono::Duration::seconds(0), true).is_err());
+        assert!(ts.ewm_by_time(chrono::Duration::seconds(-10), false).is_err());
+    }
+
+    #[test]
+    fn test_ewm_nans() {
+        let ts = TimeSeries::from_vecs(vec![0, 1, 2, 3], vec![f64::NAN, 1.0, f64::NAN, 3.0]).unwrap();
+        let means: Vec<f64> = ts.ewm(EwmDecay::alpha(0.5).unwrap(), true).map(|dp| dp.value.mean).collect();
+        assert!(means[0].is_nan());
+        assert_eq!(means[1..3], [1.0, 1.0]);
+        // the missing observation still decays the weight of the first one
+        assert_close(means[3], (0.25 * 1.0 + 3.0) / 1.25);
+    }
+
+    #[test]
+    fn test_ewm_by_time() {
+        let regular = TimeSeries::from_vecs(crate::index::HashableIndex::from_int_stamps(vec![0, 10, 20, 30]).into_values(), vec![1.0, 4.0, 2.0, 8.0]).unwrap();
+        let by_time: Vec<EwmStats> = regular.ewm_by_time(chrono::Duration::seconds(10), true).unwrap().map(|dp| dp.value).collect();
+        let by_count: Vec<EwmStats> = regular.ewm(EwmDecay::halflife(1.0).unwrap(), true).map(|dp| dp.value).collect();
+        for (a, b) in by_time.iter().zip(by_count.iter()) {
+            assert_close(a.mean, b.mean);
+            assert_close(a.var_biased, b.var_biased);
+        }
+
+        let irregular = TimeSeries::from_vecs(crate::index::HashableIndex::from_int_stamps(vec![0, 10, 30]).into_values(), vec![1.0, 2.0, 3.0]).unwrap();
+        let means: Vec<f64> = irregular.ewm_by_time(chrono::Duration::seconds(10), true).unwrap().map(|dp| dp.value.mean).collect();
+        assert_close(means[2], (0.125 * 1.0 + 0.25 * 2.0 + 3.0) / 1.375);
+    }
+
+    #[test]
+    fn test_rolling_oversized_window() {
+        let ts = TimeSeries::from_vecs(vec![1, 2, 3], vec![1.0, 2.0, 3.0]).unwrap();
+        for size in [0, 4, 10].iter() {
+            assert_eq!(ts.apply_rolling(*size, |buffer| buffer.len()).count(), 0);
+            assert_eq!(ts.apply_updating_rolling(*size, |acc: Option<f64>, x| Some(acc.unwrap_or(0.0) + x), |acc: Option<f64>, x| Some(acc.unwrap_or(0.0) - x)).count(), 0);
+        }
+        let full: TimeSeries<i32,f64> = ts.apply_updating_rolling(3, |acc: Option<f64>, x| Some(acc.unwrap_or(0.0) + x), |acc: Option<f64>, x| Some(acc.unwrap_or(0.0) - x)).collect();
+        assert_eq!(full.values(), vec![6.0]);
+        let empty: TimeSeries<i32,f64> = TimeSeries::empty();
+        assert_eq!(empty.apply_rolling(1, |buffer| buffer.len()).count(), 0);
+    }
+
+    #[test]
+    fn test_rolling_with_options() {
+        let ts = TimeSeries::from_vecs(vec![1, 2, 3, 4, 5, 6], vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+        let sum = |buffer: &[f64]| buffer.iter().sum::<f64>();
+        let values = |options: RollingOptions, size: usize| ts.apply_rolling_with(size, options, sum).map(|dp| dp.value).collect::<Vec<Option<f64>>>();
+
+        // like apply_rolling by default
+        let trailing = values(RollingOptions::default(), 3);
+        assert_eq!(trailing, vec![Some(3.0), Some(6.0), Some(9.0), Some(12.0)]);
+        let placeholders = values(RollingOptions { placeholders: true, ..Default::default() }, 3);
+        assert_eq!(placeholders, vec![None, None, Some(3.0), Some(6.0), Some(9.0), Some(12.0)]);
+        let partial = values(RollingOptions { min_periods: Some(1), ..Default::default() }, 3);
+        assert_eq!(partial, vec![Some(0.0), Some(1.0), Some(3.0), Some(6.0), Some(9.0), Some(12.0)]);
+
+        let centered = values(RollingOptions { center: true, placeholders: true, ..Default::default() }, 3);
+        assert_eq!(centered, vec![None, Some(3.0), Some(6.0), Some(9.0), Some(12.0), None]);
+        // even windows have the extra point before the center, like pandas
+        let centered_even = values(RollingOptions { center: true, placeholders: true, ..Default::default() }, 4);
+        assert_eq!(centered_even, vec![None, None, Some(6.0), Some(10.0), Some(14.0), None]);
+        let centered_partial = values(RollingOptions { center: true, min_periods: Some(2), ..Default::default() }, 3);
+        assert_eq!(centered_partial, vec![Some(1.0), Some(3.0), Some(6.0), Some(9.0), Some(12.0), Some(9.0)]);
+
+        let oversized = ts.apply_rolling_with(10, RollingOptions { placeholders: true, ..Default::default() }, sum);
+        assert!(oversized.map(|dp| dp.value).all(|v| v.is_none()));
+        assert_eq!(values(RollingOptions::default(), 10).len(), 0);
+        assert_eq!(values(RollingOptions { min_periods: Some(1), ..Default::default() }, 10)[5], Some(15.0));
+    }
+
+}
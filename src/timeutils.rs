@@ -1,5 +1,5 @@
 //! # Utilities for chrono DateTimes
-use chrono::{Duration,NaiveDateTime, DateTime, TimeZone, Utc};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, DateTime, TimeZone, Utc};
 
 ///Generate a chrono NaiveDateTime from a i64 value of milliseconds
 pub fn naive_datetime_from_millis(istamp:i64)->NaiveDateTime{
@@ -8,6 +8,11 @@ pub fn naive_datetime_from_millis(istamp:i64)->NaiveDateTime{
     NaiveDateTime::from_timestamp(secs, nsecs)
 }
 
+/// Convert a chrono Duration to a (fractional) number of seconds
+pub fn duration_as_secs_f64(duration: &Duration) -> f64 {
+    duration.num_seconds() as f64 + duration.subsec_nanos() as f64 * 1e-9
+}
+
 /// This trait defines the contract for rounding a T via the methods in timeutils
 pub trait DurationRoudable<T>{
     fn get_utc_millis_since_epoch(&self) -> i64;
@@ -35,6 +40,54 @@ impl<TZInfo: TimeZone> DurationRoudable<DateTime<TZInfo>> for DateTime<TZInfo>{
     }
 }
 
+/// This trait defines the contract for moving a T between calendar days while keeping its time of day, it is what the calendar aware index generators are built on
+pub trait CalendarDate: Sized {
+    /// the (local) calendar day
+    fn local_date(&self) -> NaiveDate;
+    /// the same time of day on another calendar day, None if that time does not exist on that day (i.e. it is skipped by a DST change)
+    fn with_local_date(&self, date: NaiveDate) -> Option<Self>;
+}
+
+impl CalendarDate for NaiveDate {
+    fn local_date(&self) -> NaiveDate {
+        *self
+    }
+    fn with_local_date(&self, date: NaiveDate) -> Option<NaiveDate> {
+        Some(date)
+    }
+}
+
+impl CalendarDate for NaiveDateTime {
+    fn local_date(&self) -> NaiveDate {
+        NaiveDateTime::date(self)
+    }
+    fn with_local_date(&self, date: NaiveDate) -> Option<NaiveDateTime> {
+        Some(date.and_time(self.time()))
+    }
+}
+
+impl<TZInfo: TimeZone> CalendarDate for DateTime<TZInfo> {
+    fn local_date(&self) -> NaiveDate {
+        self.naive_local().date()
+    }
+    fn with_local_date(&self, date: NaiveDate) -> Option<DateTime<TZInfo>> {
+        self.timezone().from_local_datetime(&date.and_time(self.naive_local().time())).earliest()
+    }
+}
+
+/// The last day of the given month, None if the month is out of the range of NaiveDate
+pub fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    match month {
+        12 => NaiveDate::from_ymd_opt(year, 12, 31),
+        _ => NaiveDate::from_ymd_opt(year, month + 1, 1).and_then(|d| d.pred_opt())
+    }
+}
+
+/// true for saturdays and sundays
+pub fn is_weekend(date: &NaiveDate) -> bool {
+    date.weekday().number_from_monday() > 5
+}
+
 //SRC:: https://stackoverflow.com/questions/31210357/is-there-a-modulus-not-remainder-function-operation
 trait ModuloSignedExt {
     fn modulo(&self, n: Self) -> Self;
@@ -106,6 +159,23 @@ mod tests {
     use super::*;
     use chrono::NaiveDate;
 
+    #[test]
+    fn test_calendar_helpers() {
+        assert_eq!(last_day_of_month(2020, 2), NaiveDate::from_ymd_opt(2020, 2, 29));
+        assert_eq!(last_day_of_month(2021, 12), NaiveDate::from_ymd_opt(2021, 12, 31));
+        assert_eq!(last_day_of_month(NaiveDate::MAX.year(), 12), Some(NaiveDate::MAX));
+        assert_eq!(last_day_of_month(NaiveDate::MAX.year() + 1, 1), None);
+        assert!(is_weekend(&NaiveDate::from_ymd_opt(2021, 1, 2).unwrap()));
+        assert!(!is_weekend(&NaiveDate::from_ymd_opt(2021, 1, 4).unwrap()));
+
+        let ndt = NaiveDate::from_ymd_opt(2021, 1, 1).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        let moved = ndt.with_local_date(NaiveDate::from_ymd_opt(2021, 3, 5).unwrap()).unwrap();
+        assert_eq!(moved, NaiveDate::from_ymd_opt(2021, 3, 5).unwrap().and_hms_opt(9, 30, 0).unwrap());
+        let dt = Utc.from_utc_datetime(&ndt);
+        assert_eq!(dt.local_date(), NaiveDate::from_ymd_opt(2021, 1, 1).unwrap());
+        assert_eq!(dt.with_local_date(NaiveDate::from_ymd_opt(2021, 3, 5).unwrap()).unwrap().naive_utc(), moved);
+    }
+
     #[test]
     fn test_rounding_up() {
 
@@ -154,4 +224,11 @@ mod tests {
 
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_duration_as_secs() {
+        assert_eq!(duration_as_secs_f64(&Duration::milliseconds(1500)), 1.5);
+        assert_eq!(duration_as_secs_f64(&Duration::milliseconds(-1500)), -1.5);
+        assert_eq!(duration_as_secs_f64(&Duration::minutes(2)), 120.0);
+    }
+
+}